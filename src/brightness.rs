@@ -0,0 +1,74 @@
+use std::{fmt::Display, str::FromStr};
+use thiserror::Error;
+
+/// Brightness of a light in percent, within the range of 10% to 100% that WiZ devices accept for
+/// `dimming`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Brightness(u8);
+
+impl Brightness {
+    /// Lowest brightness that any WiZ device accepts.
+    pub const MIN: Self = Self(10);
+    pub const MAX: Self = Self(100);
+
+    pub fn new(percent: u8) -> Result<Self, BrightnessError> {
+        if (Self::MIN.0..=Self::MAX.0).contains(&percent) {
+            Ok(Self(percent))
+        } else {
+            Err(BrightnessError::OutOfRange(percent.into()))
+        }
+    }
+
+    /// Creates a brightness from any percentage, clamping it to the valid range.
+    pub fn saturating(percent: i32) -> Self {
+        Self(percent.clamp(i32::from(Self::MIN.0), i32::from(Self::MAX.0)) as u8)
+    }
+
+    pub fn percent(&self) -> u8 {
+        self.0
+    }
+}
+
+impl TryFrom<u8> for Brightness {
+    type Error = BrightnessError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl From<Brightness> for u8 {
+    fn from(value: Brightness) -> Self {
+        value.0
+    }
+}
+
+impl Display for Brightness {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}%", self.0)
+    }
+}
+
+/// Parses a percentage such as `50` or `50%`.
+impl FromStr for Brightness {
+    type Err = BrightnessError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let percent: u32 = s
+            .trim()
+            .trim_end_matches('%')
+            .parse()
+            .map_err(|_| BrightnessError::ParseError(s.to_string()))?;
+        u8::try_from(percent)
+            .map_err(|_| BrightnessError::OutOfRange(percent))
+            .and_then(Self::new)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum BrightnessError {
+    #[error("Could not parse a brightness from \"{0}\"!")]
+    ParseError(String),
+    #[error("Brightness {0}% is outside the range of 10% to 100%!")]
+    OutOfRange(u32),
+}
@@ -0,0 +1,164 @@
+use derive_getters::Getters;
+
+use crate::{
+    brightness::Brightness,
+    connection::messages::get_model_config::GetModelConfigResponseResult,
+    devices::{BulbKind, DeviceKind},
+    products::Product,
+    scenes::Scene,
+};
+
+/// What a device is able to do, used to validate commands before they are sent.
+#[derive(Clone, Debug, Getters)]
+pub struct Capabilities {
+    dimmable: bool,
+    /// Lowest brightness the device accepts, if it is dimmable.
+    min_brightness: Brightness,
+    color: bool,
+    /// Range of color temperatures, if the device has tunable white.
+    kelvin_range: Option<KelvinRange>,
+    scenes: Vec<Scene>,
+    power_metering: bool,
+    /// Whether the device supports dynamic scenes with an adjustable speed.
+    effects: bool,
+    /// Whether the device has separate up and down lights, balanced with a ratio.
+    dual_head: bool,
+    /// Number of fan speeds, if the device has a fan.
+    fan_speeds: Option<u8>,
+    /// Ratio of white to color output, if reported by the device.
+    white_to_color_ratio: Option<u8>,
+}
+
+impl Capabilities {
+    /// Capabilities of a device of the given kind, without any information from the device itself.
+    pub fn from_kind(kind: &DeviceKind) -> Self {
+        let none = Self {
+            dimmable: false,
+            min_brightness: Brightness::MIN,
+            color: false,
+            kelvin_range: None,
+            scenes: Vec::new(),
+            power_metering: false,
+            effects: false,
+            dual_head: false,
+            fan_speeds: None,
+            white_to_color_ratio: None,
+        };
+        let tunable_white = Self {
+            dimmable: true,
+            kelvin_range: Some(KelvinRange::DEFAULT),
+            scenes: Scene::tunable_white(),
+            ..none.clone()
+        };
+        let color = Self {
+            color: true,
+            scenes: Scene::all().to_vec(),
+            effects: true,
+            ..tunable_white.clone()
+        };
+        let light = match kind {
+            // Nothing is known about the device, so let it decide which commands it accepts
+            DeviceKind::Unknown(_) => color,
+            _ => match kind.light() {
+                None => none,
+                Some(BulbKind::DimmableWhite) => Self {
+                    dimmable: true,
+                    scenes: Scene::dimmable_white(),
+                    ..none
+                },
+                Some(BulbKind::TunableWhite) => tunable_white,
+                Some(BulbKind::Color) => color,
+            },
+        };
+        match kind {
+            DeviceKind::Fan(_) => Self {
+                fan_speeds: Some(FAN_SPEEDS),
+                ..light
+            },
+            DeviceKind::DualHead(_) => Self {
+                dual_head: true,
+                ..light
+            },
+            _ => light,
+        }
+    }
+
+    /// Capabilities of a known product, which may have a different color temperature range than
+    /// other devices of its kind.
+    pub fn from_product(product: &Product) -> Self {
+        let mut capabilities = Self::from_kind(product.kind());
+        if capabilities.kelvin_range.is_some() {
+            capabilities.kelvin_range = product.kelvin_range().or(capabilities.kelvin_range);
+        }
+        capabilities
+    }
+
+    /// Refines the capabilities with the white channels and color temperature range that the
+    /// device reports in `getModelConfig`.
+    pub fn with_model_config(self, model_config: &GetModelConfigResponseResult) -> Self {
+        let mut capabilities = self;
+        capabilities.white_to_color_ratio = Some(*model_config.wcr());
+        if *model_config.nowc() >= 2 {
+            let cct_range = model_config.cct_range();
+            let min = cct_range
+                .iter()
+                .copied()
+                .filter(|kelvin| *kelvin != 0)
+                .min();
+            let max = cct_range.iter().copied().max();
+            capabilities.kelvin_range = match (min, max) {
+                (Some(min), Some(max)) if min < max => Some(KelvinRange { min, max }),
+                _ => capabilities.kelvin_range.or(Some(KelvinRange::DEFAULT)),
+            };
+        } else {
+            capabilities.kelvin_range = None;
+            if !capabilities.color {
+                capabilities.scenes = Scene::dimmable_white();
+            }
+        }
+        capabilities
+    }
+
+    /// Marks whether the device reports its power consumption, which is only known by asking it.
+    pub fn with_power_metering(mut self, value: bool) -> Self {
+        self.power_metering = value;
+        self
+    }
+
+    /// Raises the lowest brightness the device accepts, such as to the minimum dimming configured
+    /// on the device. Values below [`Brightness::MIN`] are not possible.
+    pub fn with_min_brightness(mut self, value: Brightness) -> Self {
+        self.min_brightness = value;
+        self
+    }
+
+    pub fn supports_scene(&self, scene: &Scene) -> bool {
+        self.scenes.contains(scene)
+    }
+}
+
+/// Number of speeds of WiZ fans, which are numbered from 1.
+const FAN_SPEEDS: u8 = 6;
+
+/// Inclusive range of color temperatures in Kelvin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Getters)]
+pub struct KelvinRange {
+    min: u16,
+    max: u16,
+}
+
+impl KelvinRange {
+    /// Range of most tunable white devices, used when a device does not report its own.
+    pub const DEFAULT: Self = Self {
+        min: 2200,
+        max: 6500,
+    };
+
+    pub const fn new(min: u16, max: u16) -> Self {
+        Self { min, max }
+    }
+
+    pub fn contains(&self, kelvin: u16) -> bool {
+        (self.min..=self.max).contains(&kelvin)
+    }
+}
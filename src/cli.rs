@@ -0,0 +1,10 @@
+pub mod adjustment;
+pub mod cache;
+pub mod completion;
+pub mod output;
+pub mod records;
+pub mod script;
+pub mod selector;
+pub mod signal;
+#[cfg(feature = "tui")]
+pub mod tui;
@@ -0,0 +1,96 @@
+use std::{fmt::Display, str::FromStr};
+
+use wizctl::brightness::Brightness;
+
+/// A value given on the command line, either absolute (e.g. `50`) or relative to the current
+/// value of the device (e.g. `+10` or `-10`). Relative brightness may end in `%` (e.g. `+10%`),
+/// like absolute brightness.
+#[derive(Clone, Copy, Debug)]
+pub enum Adjustment<T> {
+    To(T),
+    By(i32),
+}
+
+/// A value that can be adjusted on the command line.
+pub trait Adjustable: FromStr<Err: Display> {
+    /// Whether the value is a percentage, so that changes to it may end in `%`.
+    const PERCENTAGE: bool;
+}
+
+impl Adjustable for u16 {
+    const PERCENTAGE: bool = false;
+}
+
+impl Adjustable for Brightness {
+    const PERCENTAGE: bool = true;
+}
+
+impl<T: Display> Display for Adjustment<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::To(value) => write!(f, "{}", value),
+            Self::By(delta) => write!(f, "{:+}", delta),
+        }
+    }
+}
+
+impl<T: Adjustable> FromStr for Adjustment<T> {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.starts_with(['+', '-']) {
+            let delta = match s.strip_suffix('%') {
+                Some(_) if !T::PERCENTAGE => {
+                    return Err(format!(
+                        "Expected a change such as +10 or -10 without %, since this is not a percentage, got: {}!",
+                        s
+                    ))
+                }
+                Some(delta) => delta,
+                None => s,
+            };
+            delta
+                .parse()
+                .map(Self::By)
+                .map_err(|_| format!("Expected a change such as +10 or -10, got: {}!", s))
+        } else {
+            s.parse().map(Self::To).map_err(|e| {
+                format!(
+                    "Expected a value, or a change such as +10 or -10, got: {}!\n{}",
+                    s, e
+                )
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_relative_brightness_percentages() {
+        for (input, expected) in [("+10%", 10), ("-25%", -25), ("+10", 10)] {
+            assert!(
+                matches!(input.parse::<Adjustment<Brightness>>(), Ok(Adjustment::By(delta)) if delta == expected),
+                "{input}"
+            );
+        }
+        assert!(matches!(
+            "50%".parse::<Adjustment<Brightness>>(),
+            Ok(Adjustment::To(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_percentages_of_other_values() {
+        assert!(matches!(
+            "+100".parse::<Adjustment<u16>>(),
+            Ok(Adjustment::By(100))
+        ));
+        for input in ["+10%", "-10%", "2700%"] {
+            assert!(input.parse::<Adjustment<u16>>().is_err(), "{input}");
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    net::IpAddr,
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+use wizctl::devices::Device;
+
+/// Devices found by the last `wizctl list`, stored as JSON in `devices.json` in the wizctl cache
+/// directory (e.g. `~/.cache/wizctl/devices.json`), so that shell completion can suggest them
+/// without discovering devices.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct DeviceCache(Vec<CachedDevice>);
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CachedDevice {
+    pub ip: IpAddr,
+    pub mac: String,
+    pub model_name: String,
+}
+
+impl DeviceCache {
+    pub fn new(devices: &[Device]) -> Self {
+        Self(
+            devices
+                .iter()
+                .map(|device| CachedDevice {
+                    ip: *device.ip(),
+                    mac: device.mac().to_string(),
+                    model_name: device.fetch_model_name(),
+                })
+                .collect(),
+        )
+    }
+
+    pub fn path() -> Result<PathBuf, CacheError> {
+        dirs::cache_dir()
+            .map(|dir| dir.join("wizctl").join("devices.json"))
+            .ok_or(CacheError::NoCacheDir)
+    }
+
+    /// Reads the cache, which is treated as empty if it is missing or cannot be read, since it
+    /// only serves as a hint.
+    pub fn load() -> Self {
+        Self::path()
+            .ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), CacheError> {
+        let path = Self::path()?;
+        let write = |path: &Path| {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            let json = serde_json::to_string_pretty(self).expect("failed to serialize devices");
+            fs::write(path, json + "\n")
+        };
+        write(&path).map_err(|source| CacheError::CacheFileError { path, source })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &CachedDevice> {
+        self.0.iter()
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum CacheError {
+    #[error("Could not find a cache directory for the device cache!")]
+    NoCacheDir,
+    #[error("Could not write the device cache at {path}!\n{source}", path = path.display())]
+    CacheFileError { path: PathBuf, source: io::Error },
+}
@@ -0,0 +1,52 @@
+use clap_complete::{env::Shells, CompletionCandidate, Shell};
+use std::{env, io};
+
+use super::{cache::DeviceCache, selector::Aliases};
+
+/// Writes the script that a shell sources to complete wizctl commands. The script asks wizctl
+/// itself for completions, so that device names are looked up when Tab is pressed.
+pub fn write_registration(shell: Shell, buf: &mut dyn io::Write) -> io::Result<()> {
+    let completer = env::args().next().unwrap_or_else(|| "wizctl".to_string());
+    Shells::builtins()
+        .completer(&shell.to_string())
+        .ok_or_else(|| io::Error::other(format!("Completions for {} are not supported!", shell)))?
+        .write_registration("COMPLETE", "wizctl", "wizctl", &completer, buf)
+}
+
+/// Aliases, followed by the IP addresses of devices in the device cache that have no alias.
+pub fn device_candidates() -> Vec<CompletionCandidate> {
+    let aliases = Aliases::load().unwrap_or_default();
+    let mut candidates = alias_candidates_of(&aliases);
+    candidates.extend(
+        DeviceCache::load()
+            .iter()
+            .filter(|device| aliases.iter().all(|(_, ip)| *ip != device.ip))
+            .map(|device| {
+                CompletionCandidate::new(device.ip.to_string())
+                    .help(Some(device.model_name.clone().into()))
+            }),
+    );
+    candidates
+}
+
+/// IP addresses of the devices in the device cache.
+pub fn ip_candidates() -> Vec<CompletionCandidate> {
+    DeviceCache::load()
+        .iter()
+        .map(|device| {
+            CompletionCandidate::new(device.ip.to_string())
+                .help(Some(device.model_name.clone().into()))
+        })
+        .collect()
+}
+
+pub fn alias_candidates() -> Vec<CompletionCandidate> {
+    alias_candidates_of(&Aliases::load().unwrap_or_default())
+}
+
+fn alias_candidates_of(aliases: &Aliases) -> Vec<CompletionCandidate> {
+    aliases
+        .iter()
+        .map(|(name, ip)| CompletionCandidate::new(name).help(Some(ip.to_string().into())))
+        .collect()
+}
@@ -0,0 +1,63 @@
+use clap::ValueEnum;
+use serde::Serialize;
+use std::io;
+use thiserror::Error;
+
+/// How results are printed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable tables and messages.
+    #[default]
+    Table,
+    /// A single JSON document.
+    Json,
+    /// One JSON object per line.
+    Jsonl,
+    /// Comma-separated values with a header row.
+    Csv,
+}
+
+impl OutputFormat {
+    pub fn is_table(&self) -> bool {
+        *self == Self::Table
+    }
+
+    /// Prints records in this format. Tables are rendered by `table`, since each command lays out
+    /// its own, and nothing is printed if it is empty.
+    pub fn print<T: Serialize>(
+        &self,
+        records: &[T],
+        table: impl FnOnce(&[T]) -> String,
+    ) -> Result<(), OutputError> {
+        match self {
+            Self::Table => {
+                let table = table(records);
+                if !table.is_empty() {
+                    println!("{}", table);
+                }
+            }
+            Self::Json => println!("{}", serde_json::to_string_pretty(records)?),
+            Self::Jsonl => {
+                for record in records {
+                    println!("{}", serde_json::to_string(record)?);
+                }
+            }
+            Self::Csv => {
+                let mut writer = csv::Writer::from_writer(io::stdout());
+                for record in records {
+                    writer.serialize(record)?;
+                }
+                writer.flush().map_err(csv::Error::from)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum OutputError {
+    #[error("Could not write JSON output!\n{0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Could not write CSV output!\n{0}")]
+    Csv(#[from] csv::Error),
+}
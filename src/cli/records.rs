@@ -0,0 +1,132 @@
+use serde::Serialize;
+use std::net::IpAddr;
+use wizctl::devices::Device;
+
+/// A discovered device and its current state, as printed by `wizctl list`.
+#[derive(Serialize)]
+pub struct DeviceRecord {
+    pub mac: String,
+    pub ip: IpAddr,
+    pub interface: Option<String>,
+    pub kind: String,
+    pub module_name: String,
+    pub model_name: String,
+    pub firmware_version: String,
+    pub rssi: Option<i8>,
+    pub on: Option<bool>,
+    pub brightness: Option<u8>,
+    pub color: Option<String>,
+    pub scene: Option<String>,
+}
+
+impl DeviceRecord {
+    /// Describes a device, asking it for its current state. State fields are empty if it does not
+    /// respond.
+    pub fn new(device: &Device) -> Self {
+        let state = device.get_state().ok();
+        Self {
+            mac: device.mac().to_string(),
+            ip: *device.ip(),
+            interface: device.interface().map(str::to_string),
+            kind: device.kind().to_string(),
+            module_name: device.module_name().to_string(),
+            model_name: device.fetch_model_name(),
+            firmware_version: device.firmware_version().to_string(),
+            rssi: state.as_ref().map(|state| *state.rssi()),
+            on: state.as_ref().map(|state| *state.on()),
+            brightness: state.as_ref().and_then(|state| *state.brightness()),
+            color: state
+                .as_ref()
+                .and_then(|state| state.color().as_ref().map(ToString::to_string)),
+            scene: state
+                .as_ref()
+                .and_then(|state| state.scene().map(|scene| scene.to_string())),
+        }
+    }
+}
+
+/// Result of changing a device with `wizctl set` or `wizctl fan`. Only the requested changes are
+/// filled in.
+#[derive(Serialize)]
+pub struct ChangeRecord {
+    pub ip: IpAddr,
+    pub mac: Option<String>,
+    pub success: bool,
+    pub error: Option<String>,
+    pub on: Option<bool>,
+    pub color: Option<String>,
+    pub scene: Option<String>,
+    pub kelvin: Option<u16>,
+    pub kelvin_change: Option<i32>,
+    pub brightness: Option<u8>,
+    pub brightness_change: Option<i32>,
+    pub ratio: Option<u8>,
+    pub fan_on: Option<bool>,
+    pub fan_speed: Option<u8>,
+    pub fan_reverse: Option<bool>,
+    pub fan_mode: Option<String>,
+}
+
+impl ChangeRecord {
+    pub fn new(ip: IpAddr) -> Self {
+        Self {
+            ip,
+            mac: None,
+            success: false,
+            error: None,
+            on: None,
+            color: None,
+            scene: None,
+            kelvin: None,
+            kelvin_change: None,
+            brightness: None,
+            brightness_change: None,
+            ratio: None,
+            fan_on: None,
+            fan_speed: None,
+            fan_reverse: None,
+            fan_mode: None,
+        }
+    }
+}
+
+/// A single field of the state of a device, as printed by `wizctl get`.
+#[derive(Serialize)]
+pub struct FieldRecord {
+    pub ip: IpAddr,
+    pub field: String,
+    pub value: serde_json::Value,
+}
+
+/// A name for a device.
+#[derive(Serialize)]
+pub struct AliasRecord {
+    pub name: String,
+    pub ip: IpAddr,
+}
+
+/// A single user configuration setting of a device.
+#[derive(Serialize)]
+pub struct SettingRecord {
+    pub ip: IpAddr,
+    pub setting: String,
+    pub value: String,
+}
+
+/// A one-off action performed on a device, such as rebooting it.
+#[derive(Serialize)]
+pub struct ActionRecord {
+    pub ip: IpAddr,
+    pub mac: String,
+    pub action: &'static str,
+}
+
+impl ActionRecord {
+    pub fn new(device: &Device, action: &'static str) -> Self {
+        Self {
+            ip: *device.ip(),
+            mac: device.mac().to_string(),
+            action,
+        }
+    }
+}
@@ -0,0 +1,322 @@
+use clap::Parser;
+use std::{
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use thiserror::Error;
+use wizctl::{brightness::Brightness, duration::parse_duration};
+
+use super::selector::Selector;
+
+/// A line of a script, numbered as in the file for error messages. Lines that make up the body of
+/// a `repeat` are nested in it.
+pub struct Line<C> {
+    pub number: usize,
+    /// Whether a failure is reported and then ignored, as requested by starting the line with
+    /// `try`.
+    pub ignore_errors: bool,
+    pub statement: Statement<C>,
+}
+
+pub enum Statement<C> {
+    /// A wizctl command, such as `set kitchen --on`.
+    Command(C),
+    Sleep(Duration),
+    /// Runs the body `count` times, or until wizctl is stopped if there is no count.
+    Repeat {
+        count: Option<u32>,
+        body: Vec<Line<C>>,
+    },
+    /// Remembers the state of a device so that it can be restored later in the script.
+    Snapshot(Selector),
+    Restore(Selector),
+    Fade(FadeArgs),
+}
+
+/// Gradually changes the brightness and color temperature of a device.
+#[derive(Parser)]
+#[command(name = "fade", no_binary_name = true)]
+pub struct FadeArgs {
+    #[clap(help = "IP address, MAC address or alias of the device to fade")]
+    pub device: Selector,
+
+    #[clap(long, help = "Brightness in percent to fade to")]
+    pub brightness: Option<Brightness>,
+
+    #[clap(long, help = "Color temperature in Kelvin to fade to")]
+    pub kelvin: Option<u16>,
+
+    #[clap(long, value_parser = parse_duration, help = "How long the fade takes (e.g. \"5s\")")]
+    pub over: Duration,
+}
+
+/// Reads a script from a file, or from stdin if `path` is `-`.
+pub fn read(path: &Path) -> Result<String, ScriptError> {
+    let result = if path == Path::new("-") {
+        let mut text = String::new();
+        io::stdin().read_to_string(&mut text).map(|_| text)
+    } else {
+        fs::read_to_string(path)
+    };
+    result.map_err(|source| ScriptError::UnreadableScript {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Parses a script with one statement per line. Words are separated by spaces and can be quoted
+/// with `"` or `'`, and lines starting with `#` are comments. Any line that is not one of the
+/// statements of the script language is parsed by `parse_command`, so that scripts can use the
+/// same commands as the command line:
+///
+/// ```text
+/// # Wake up slowly, then flash the porch light three times
+/// set bedroom --kelvin 2700 --brightness 10
+/// fade bedroom --brightness 100 --kelvin 4000 --over 10m
+/// snapshot porch
+/// repeat 3
+///     toggle porch
+///     sleep 500ms
+/// end
+/// try restore porch
+/// ```
+pub fn parse<C>(
+    text: &str,
+    parse_command: impl Fn(&[String]) -> Result<C, String>,
+) -> Result<Vec<Line<C>>, ScriptError> {
+    let mut lines = Vec::new();
+    // Repeats that have not been closed yet, innermost last
+    let mut repeats: Vec<OpenRepeat<C>> = Vec::new();
+
+    for (i, text) in text.lines().enumerate() {
+        let number = i + 1;
+        let error = |message: String| ScriptError::InvalidLine {
+            line: number,
+            message,
+        };
+        if text.trim_start().starts_with('#') {
+            continue;
+        }
+        let mut words = split_words(text).map_err(error)?;
+        if words.is_empty() {
+            continue;
+        }
+        let ignore_errors = words[0] == "try";
+        if ignore_errors {
+            words.remove(0);
+            if words.is_empty() {
+                return Err(error("Expected a command after try!".to_string()));
+            }
+        }
+
+        let argument = || match &words[1..] {
+            [argument] => Ok(argument.as_str()),
+            _ => Err(error(format!("{} expects a single argument!", words[0]))),
+        };
+        let statement = match words[0].as_str() {
+            "repeat" => {
+                let count = match &words[1..] {
+                    [] => None,
+                    [count] => Some(count.parse().map_err(|_| {
+                        error(format!("Expected a number of repetitions, got: {}!", count))
+                    })?),
+                    _ => return Err(error("repeat expects at most one argument!".to_string())),
+                };
+                repeats.push(OpenRepeat {
+                    number,
+                    ignore_errors,
+                    count,
+                    body: Vec::new(),
+                });
+                continue;
+            }
+            "end" => {
+                if words.len() > 1 || ignore_errors {
+                    return Err(error("end must be on a line of its own!".to_string()));
+                }
+                let repeat = repeats.pop().ok_or(ScriptError::UnexpectedEnd(number))?;
+                let line = Line {
+                    number: repeat.number,
+                    ignore_errors: repeat.ignore_errors,
+                    statement: Statement::Repeat {
+                        count: repeat.count,
+                        body: repeat.body,
+                    },
+                };
+                match repeats.last_mut() {
+                    Some(repeat) => repeat.body.push(line),
+                    None => lines.push(line),
+                }
+                continue;
+            }
+            "sleep" | "wait" => {
+                Statement::Sleep(parse_duration(argument()?).map_err(|e| error(e.to_string()))?)
+            }
+            "snapshot" => {
+                Statement::Snapshot(argument()?.parse().map_err(|e| error(format!("{}", e)))?)
+            }
+            "restore" => {
+                Statement::Restore(argument()?.parse().map_err(|e| error(format!("{}", e)))?)
+            }
+            "fade" => Statement::Fade(
+                FadeArgs::try_parse_from(&words[1..]).map_err(|e| error(clap_message(&e)))?,
+            ),
+            _ => Statement::Command(parse_command(&words).map_err(error)?),
+        };
+        let line = Line {
+            number,
+            ignore_errors,
+            statement,
+        };
+        match repeats.last_mut() {
+            Some(repeat) => repeat.body.push(line),
+            None => lines.push(line),
+        }
+    }
+
+    match repeats.last() {
+        Some(repeat) => Err(ScriptError::UnterminatedRepeat(repeat.number)),
+        None => Ok(lines),
+    }
+}
+
+/// A `repeat` whose `end` has not been reached yet.
+struct OpenRepeat<C> {
+    number: usize,
+    ignore_errors: bool,
+    count: Option<u32>,
+    body: Vec<Line<C>>,
+}
+
+/// Gets the first line of a clap error, without the usage and help hints that do not apply to
+/// scripts.
+pub fn clap_message(error: &clap::Error) -> String {
+    let message = error.to_string();
+    let message = message.lines().next().unwrap_or_default();
+    message
+        .strip_prefix("error: ")
+        .unwrap_or(message)
+        .to_string()
+}
+
+fn split_words(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quote = None;
+    for c in line.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => word.get_or_insert_with(String::new).push(c),
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            None if c.is_whitespace() => words.extend(word.take()),
+            None => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if let Some(q) = quote {
+        return Err(format!("Missing closing {}!", q));
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+#[derive(Debug, Error)]
+pub enum ScriptError {
+    #[error("Could not read the script from {path}!\n{source}", path = path.display())]
+    UnreadableScript { path: PathBuf, source: io::Error },
+    #[error("Line {line}: {message}")]
+    InvalidLine { line: usize, message: String },
+    #[error("Line {0}: repeat is never closed with end!")]
+    UnterminatedRepeat(usize),
+    #[error("Line {0}: end does not close a repeat!")]
+    UnexpectedEnd(usize),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_script(text: &str) -> Result<Vec<Line<String>>, ScriptError> {
+        parse(text, |words| Ok(words.join(" ")))
+    }
+
+    #[test]
+    fn splits_words() {
+        assert_eq!(
+            split_words("  set  porch --on ").unwrap(),
+            ["set", "porch", "--on"]
+        );
+        assert_eq!(
+            split_words(r#"call porch setPilot '{"state": true}'"#).unwrap(),
+            ["call", "porch", "setPilot", r#"{"state": true}"#]
+        );
+        assert_eq!(
+            split_words(r#"alias set "" 'it''s'"#).unwrap(),
+            ["alias", "set", "", "its"]
+        );
+        assert!(split_words("set \"porch").is_err());
+        assert!(split_words("set 'porch").is_err());
+    }
+
+    #[test]
+    fn parses_statements() {
+        let lines = parse_script(
+            "# Flash the porch light\n\
+             \n\
+             snapshot porch\n\
+             repeat 3\n\
+             \x20   # Comments can be indented\n\
+             \x20   try toggle porch\n\
+             \x20   sleep 500ms\n\
+             end\n\
+             restore porch\n",
+        )
+        .unwrap();
+        assert_eq!(lines.len(), 3);
+        assert!(
+            matches!(&lines[0].statement, Statement::Snapshot(Selector::Alias(name)) if name == "porch")
+        );
+        assert_eq!(lines[1].number, 4);
+        let Statement::Repeat { count, body } = &lines[1].statement else {
+            panic!("expected a repeat");
+        };
+        assert_eq!(*count, Some(3));
+        assert!(body[0].ignore_errors);
+        assert!(
+            matches!(&body[0].statement, Statement::Command(command) if command == "toggle porch")
+        );
+        assert!(
+            matches!(body[1].statement, Statement::Sleep(duration) if duration == Duration::from_millis(500))
+        );
+        assert!(!lines[2].ignore_errors);
+    }
+
+    #[test]
+    fn rejects_invalid_scripts() {
+        let line_of = |text: &str| match parse_script(text) {
+            Err(ScriptError::InvalidLine { line, .. }) => line,
+            Err(e) => panic!("unexpected error for {text:?}: {e}"),
+            Ok(_) => panic!("{text:?} should not parse"),
+        };
+        assert_eq!(line_of("sleep 1s\nsleep soon"), 2);
+        assert_eq!(line_of("sleep 2"), 1);
+        assert_eq!(line_of("sleep 1s 2s"), 1);
+        assert_eq!(line_of("try"), 1);
+        assert_eq!(line_of("repeat many\nend"), 1);
+        assert_eq!(line_of("repeat\nend now"), 2);
+        assert_eq!(line_of("set \"porch --on"), 1);
+        assert_eq!(line_of("fade porch --brightness 50"), 1);
+        assert!(matches!(
+            parse_script("repeat\n  toggle porch"),
+            Err(ScriptError::UnterminatedRepeat(1))
+        ));
+        assert!(matches!(
+            parse_script("toggle porch\nend"),
+            Err(ScriptError::UnexpectedEnd(2))
+        ));
+    }
+}
@@ -0,0 +1,286 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    fs, io,
+    net::IpAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use thiserror::Error;
+
+use super::cache::{CachedDevice, DeviceCache};
+
+/// A device named on the command line, by its IP address, its MAC address or an alias.
+#[derive(Clone, Debug)]
+pub enum Selector {
+    Ip(IpAddr),
+    /// Lowercase MAC address without separators, as devices report it.
+    Mac(String),
+    Alias(String),
+}
+
+impl Selector {
+    /// Finds the IP address of the device, looking MAC addresses up in the devices found by the
+    /// last `wizctl list` and aliases up in the alias file.
+    pub fn resolve(&self) -> Result<IpAddr, SelectorError> {
+        match self {
+            Self::Ip(ip) => Ok(*ip),
+            Self::Mac(mac) => find_mac(DeviceCache::load().iter(), mac),
+            Self::Alias(name) => find_alias(&Aliases::load()?, name),
+        }
+    }
+}
+
+impl Display for Selector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ip(ip) => write!(f, "{}", ip),
+            Self::Mac(mac) => write!(f, "{}", mac),
+            Self::Alias(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// Parses an IP address, a MAC address (e.g. `a8:bb:50:12:34:56`, `A8-BB-50-12-34-56` or
+/// `a8bb50123456`), or else an alias name.
+impl FromStr for Selector {
+    type Err = SelectorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(ip) = s.parse() {
+            Ok(Self::Ip(ip))
+        } else if let Some(mac) = parse_mac(s) {
+            Ok(Self::Mac(mac))
+        } else {
+            Ok(Self::Alias(parse_alias_name(s)?))
+        }
+    }
+}
+
+/// Normalizes a MAC address with `:` or `-` between every byte, or none at all.
+fn parse_mac(s: &str) -> Option<String> {
+    let mac = s.replace([':', '-'], "").to_ascii_lowercase();
+    let separated = s.len() == 17 && s.split([':', '-']).all(|byte| byte.len() == 2);
+    (mac.len() == 12 && mac.chars().all(|c| c.is_ascii_hexdigit()) && (separated || s.len() == 12))
+        .then_some(mac)
+}
+
+/// Finds the IP address of the device with the given MAC address. A device can be listed under
+/// several addresses, e.g. once over IPv4 and once over IPv6, in which case it is ambiguous.
+fn find_mac<'a>(
+    devices: impl Iterator<Item = &'a CachedDevice>,
+    mac: &str,
+) -> Result<IpAddr, SelectorError> {
+    let mut ips: Vec<IpAddr> = devices
+        .filter(|device| device.mac.eq_ignore_ascii_case(mac))
+        .map(|device| device.ip)
+        .collect();
+    ips.sort();
+    ips.dedup();
+    match ips[..] {
+        [ip] => Ok(ip),
+        [] => Err(SelectorError::UnknownMac(mac.to_string())),
+        _ => Err(SelectorError::AmbiguousMac {
+            mac: mac.to_string(),
+            ips: ips
+                .iter()
+                .map(IpAddr::to_string)
+                .collect::<Vec<_>>()
+                .join(", "),
+        }),
+    }
+}
+
+fn find_alias(aliases: &Aliases, name: &str) -> Result<IpAddr, SelectorError> {
+    aliases
+        .get(name)
+        .ok_or_else(|| SelectorError::UnknownAlias(name.to_string()))
+}
+
+/// Checks that an alias name cannot be mistaken for an IP address or a command-line flag.
+pub fn parse_alias_name(s: &str) -> Result<String, SelectorError> {
+    let valid = s
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+        && s.chars().next().is_some_and(char::is_alphabetic);
+    if valid {
+        Ok(s.to_string())
+    } else {
+        Err(SelectorError::InvalidSelector(s.to_string()))
+    }
+}
+
+/// Names for devices, stored as a JSON object of names to IP addresses in `aliases.json` in the
+/// wizctl config directory (e.g. `~/.config/wizctl/aliases.json`).
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Aliases(BTreeMap<String, IpAddr>);
+
+impl Aliases {
+    pub fn path() -> Result<PathBuf, SelectorError> {
+        dirs::config_dir()
+            .map(|dir| dir.join("wizctl").join("aliases.json"))
+            .ok_or(SelectorError::NoConfigDir)
+    }
+
+    /// Reads the alias file, which is treated as empty if it does not exist yet.
+    pub fn load() -> Result<Self, SelectorError> {
+        let path = Self::path()?;
+        match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json)
+                .map_err(|source| SelectorError::InvalidAliasFile { path, source }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(source) => Err(SelectorError::AliasFileError { path, source }),
+        }
+    }
+
+    pub fn save(&self) -> Result<(), SelectorError> {
+        let path = Self::path()?;
+        let write = |path: &Path| {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            let json = serde_json::to_string_pretty(self).expect("failed to serialize aliases");
+            fs::write(path, json + "\n")
+        };
+        write(&path).map_err(|source| SelectorError::AliasFileError { path, source })
+    }
+
+    pub fn get(&self, name: &str) -> Option<IpAddr> {
+        self.0.get(name).copied()
+    }
+
+    pub fn insert(&mut self, name: String, ip: IpAddr) {
+        self.0.insert(name, ip);
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<IpAddr> {
+        self.0.remove(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &IpAddr)> {
+        self.0.iter()
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SelectorError {
+    #[error("Expected an IP address, a MAC address or an alias made of letters, digits, - and _, got: {0}!")]
+    InvalidSelector(String),
+    #[error("No device is called {0}! Use `wizctl alias set` to name a device")]
+    UnknownAlias(String),
+    #[error("No device with MAC address {0} was found by the last `wizctl list`! Run it again to refresh the devices")]
+    UnknownMac(String),
+    #[error(
+        "Several devices were found with MAC address {mac}: {ips}! Select one by its IP address"
+    )]
+    AmbiguousMac { mac: String, ips: String },
+    #[error("Could not find a config directory for the alias file!")]
+    NoConfigDir,
+    #[error("Could not access the alias file at {path}!\n{source}", path = path.display())]
+    AliasFileError { path: PathBuf, source: io::Error },
+    #[error("Could not parse the alias file at {path}!\n{source}", path = path.display())]
+    InvalidAliasFile {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cached(ip: &str, mac: &str) -> CachedDevice {
+        CachedDevice {
+            ip: ip.parse().unwrap(),
+            mac: mac.to_string(),
+            model_name: "WiZ A60".to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_ip_addresses() {
+        for ip in ["192.168.1.20", "fe80::1", "::1"] {
+            assert!(
+                matches!(ip.parse::<Selector>().unwrap(), Selector::Ip(parsed) if parsed == ip.parse::<IpAddr>().unwrap()),
+                "{ip}"
+            );
+        }
+    }
+
+    #[test]
+    fn parses_mac_addresses() {
+        for mac in ["a8:bb:50:12:34:56", "A8-BB-50-12-34-56", "a8bb50123456"] {
+            assert!(
+                matches!(mac.parse::<Selector>().unwrap(), Selector::Mac(parsed) if parsed == "a8bb50123456"),
+                "{mac}"
+            );
+        }
+        for not_mac in ["a8bb5012345", "a8:bb:50:12:34:5g", "a8b:b50:12:34:56"] {
+            assert!(
+                !matches!(not_mac.parse::<Selector>(), Ok(Selector::Mac(_))),
+                "{not_mac}"
+            );
+        }
+    }
+
+    #[test]
+    fn parses_aliases() {
+        for alias in ["kitchen", "porch-light", "bed_2"] {
+            assert!(
+                matches!(alias.parse::<Selector>().unwrap(), Selector::Alias(parsed) if parsed == alias),
+                "{alias}"
+            );
+        }
+        for invalid in ["", "2nd-floor", "-v", "living room", "192.168.1.300"] {
+            assert!(
+                matches!(
+                    invalid.parse::<Selector>(),
+                    Err(SelectorError::InvalidSelector(_))
+                ),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn finds_aliases() {
+        let mut aliases = Aliases::default();
+        aliases.insert("kitchen".to_string(), "192.168.1.20".parse().unwrap());
+        assert_eq!(
+            find_alias(&aliases, "kitchen").unwrap(),
+            "192.168.1.20".parse::<IpAddr>().unwrap()
+        );
+        assert!(matches!(
+            find_alias(&aliases, "porch"),
+            Err(SelectorError::UnknownAlias(_))
+        ));
+    }
+
+    #[test]
+    fn finds_mac_addresses() {
+        let devices = [
+            cached("192.168.1.20", "a8bb50123456"),
+            cached("192.168.1.21", "a8bb50abcdef"),
+            cached("fd00::21", "a8bb50abcdef"),
+            cached("192.168.1.22", "a8bb50000000"),
+            cached("192.168.1.22", "a8bb50000000"),
+        ];
+        assert_eq!(
+            find_mac(devices.iter(), "a8bb50123456").unwrap(),
+            "192.168.1.20".parse::<IpAddr>().unwrap()
+        );
+        assert_eq!(
+            find_mac(devices.iter(), "a8bb50000000").unwrap(),
+            "192.168.1.22".parse::<IpAddr>().unwrap()
+        );
+        assert!(matches!(
+            find_mac(devices.iter(), "a8bb50abcdef"),
+            Err(SelectorError::AmbiguousMac { .. })
+        ));
+        assert!(matches!(
+            find_mac(devices.iter(), "a8bb50ffffff"),
+            Err(SelectorError::UnknownMac(_))
+        ));
+    }
+}
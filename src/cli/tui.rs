@@ -0,0 +1,395 @@
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Flex, Layout, Rect},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, Cell, Clear, List, ListState, Row, Table, TableState},
+    DefaultTerminal, Frame,
+};
+use std::{
+    collections::HashMap,
+    io,
+    net::IpAddr,
+    time::{Duration, Instant},
+};
+use thiserror::Error;
+use wizctl::{
+    connection::ConnectionBuilder,
+    devices::{Device, DeviceError, SetPilotBuilder},
+    scenes::Scene,
+    state::LightState,
+};
+
+use super::{selector::Aliases, signal::rssi_to_signal_strength};
+
+/// Change in brightness, in percent, for each press of `+` or `-`.
+const BRIGHTNESS_STEP: i32 = 10;
+/// Change in color temperature, in Kelvin, for each press of `[` or `]`.
+const KELVIN_STEP: i32 = 250;
+/// How often the state of the next device is refreshed. Devices are refreshed one at a time so
+/// that an unresponsive device does not freeze the dashboard for long.
+const REFRESH_INTERVAL: Duration = Duration::from_millis(250);
+
+const HELP: &str = "↑/↓ select  space toggle  +/- brightness  [/] warmer/cooler  s scene  g group by room  r rediscover  q quit";
+
+/// Discovers devices with `connection_builder` and shows them in a live dashboard until the user
+/// quits.
+pub fn run(connection_builder: &ConnectionBuilder) -> Result<(), TuiError> {
+    eprintln!("Discovering devices...");
+    let mut dashboard = Dashboard::new(connection_builder.clone());
+    dashboard.discover()?;
+
+    let mut terminal = ratatui::init();
+    let result = dashboard.run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+/// A device shown on the dashboard, with the last state it reported.
+struct Entry {
+    device: Device,
+    name: String,
+    state: Result<LightState, String>,
+}
+
+impl Entry {
+    fn refresh(&mut self) {
+        self.state = self.device.get_state().map_err(|e| one_line(&e));
+    }
+}
+
+struct Dashboard {
+    connection_builder: ConnectionBuilder,
+    entries: Vec<Entry>,
+    selected: usize,
+    by_room: bool,
+    /// Scenes of the selected device, while the scene picker is open.
+    scene_picker: Option<(Vec<Scene>, ListState)>,
+    next_refresh: usize,
+    status: String,
+    quit: bool,
+}
+
+impl Dashboard {
+    fn new(connection_builder: ConnectionBuilder) -> Self {
+        Self {
+            connection_builder,
+            entries: Vec::new(),
+            selected: 0,
+            by_room: false,
+            scene_picker: None,
+            next_refresh: 0,
+            status: String::new(),
+            quit: false,
+        }
+    }
+
+    /// Replaces the devices on the dashboard with those currently on the network, naming them by
+    /// their aliases where they have one. Their capabilities are loaded up front so that drawing
+    /// the dashboard does not wait on the network. The devices are kept if discovery fails.
+    fn discover(&mut self) -> Result<(), DeviceError> {
+        let aliases: HashMap<IpAddr, String> = Aliases::load()
+            .map(|aliases| {
+                aliases
+                    .iter()
+                    .map(|(name, ip)| (*ip, name.clone()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut devices = Vec::new();
+        let mut skipped = 0;
+        for device in Device::discover_with(&self.connection_builder)? {
+            match device {
+                Ok(device) => devices.push(device),
+                Err(_) => skipped += 1,
+            }
+        }
+        Device::load_capabilities(&devices);
+
+        self.entries.clear();
+        for device in devices {
+            let name = aliases
+                .get(device.ip())
+                .cloned()
+                .unwrap_or_else(|| device.mac().to_string());
+            let mut entry = Entry {
+                device,
+                name,
+                state: Err(String::new()),
+            };
+            entry.refresh();
+            self.entries.push(entry);
+        }
+        self.sort();
+        self.selected = 0;
+        self.status = format!("Found {} devices", self.entries.len());
+        if skipped > 0 {
+            self.status += &format!(", skipped {} that could not be set up", skipped);
+        }
+        Ok(())
+    }
+
+    /// Orders the devices by IP address, or by room and then IP address, keeping the same device
+    /// selected.
+    fn sort(&mut self) {
+        let selected_mac = self
+            .entries
+            .get(self.selected)
+            .map(|entry| entry.device.mac().to_string());
+        let by_room = self.by_room;
+        self.entries.sort_by_key(|entry| {
+            let room = if by_room { entry.device.room_id() } else { 0 };
+            (room, *entry.device.ip())
+        });
+        if let Some(mac) = selected_mac {
+            self.selected = self
+                .entries
+                .iter()
+                .position(|entry| entry.device.mac() == mac)
+                .unwrap_or(0);
+        }
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<(), TuiError> {
+        let mut last_refresh = Instant::now();
+        while !self.quit {
+            terminal.draw(|frame| self.draw(frame))?;
+            if event::poll(REFRESH_INTERVAL)? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.handle_key(key.code)?;
+                    }
+                }
+            }
+            if last_refresh.elapsed() >= REFRESH_INTERVAL && !self.entries.is_empty() {
+                self.next_refresh %= self.entries.len();
+                self.entries[self.next_refresh].refresh();
+                self.next_refresh += 1;
+                last_refresh = Instant::now();
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_key(&mut self, code: KeyCode) -> Result<(), TuiError> {
+        if let Some((scenes, list_state)) = &mut self.scene_picker {
+            match code {
+                KeyCode::Up | KeyCode::Char('k') => list_state.select_previous(),
+                KeyCode::Down | KeyCode::Char('j') => list_state.select_next(),
+                KeyCode::Enter | KeyCode::Char(' ') => {
+                    let scene = list_state.selected().and_then(|i| scenes.get(i)).copied();
+                    self.scene_picker = None;
+                    if let Some(scene) = scene {
+                        self.change(&format!("Set the {} scene on", scene), |builder| {
+                            builder.scene(scene)
+                        });
+                    }
+                }
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('s') => self.scene_picker = None,
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        match code {
+            KeyCode::Esc | KeyCode::Char('q') => self.quit = true,
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1).min(self.entries.len().saturating_sub(1))
+            }
+            KeyCode::Enter | KeyCode::Char(' ') => self.change("Toggled", |builder| {
+                builder.toggle().map(|(builder, _)| builder)
+            }),
+            KeyCode::Char('+') | KeyCode::Char('=') => self.change("Brightened", |builder| {
+                builder.on().brightness_by(BRIGHTNESS_STEP)
+            }),
+            KeyCode::Char('-') => self.change("Dimmed", |builder| {
+                builder.on().brightness_by(-BRIGHTNESS_STEP)
+            }),
+            KeyCode::Char('[') => self.change("Made warmer", |builder| {
+                builder.on().kelvin_by(-KELVIN_STEP)
+            }),
+            KeyCode::Char(']') => {
+                self.change("Made cooler", |builder| builder.on().kelvin_by(KELVIN_STEP))
+            }
+            KeyCode::Char('s') => self.open_scene_picker(),
+            KeyCode::Char('g') => {
+                self.by_room = !self.by_room;
+                self.sort();
+            }
+            KeyCode::Char('r') => {
+                if let Err(e) = self.discover() {
+                    self.status = format!("Could not discover devices: {}", one_line(&e));
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn open_scene_picker(&mut self) {
+        let Some(entry) = self.entries.get(self.selected) else {
+            return;
+        };
+        let scenes = entry.device.capabilities().scenes().clone();
+        if scenes.is_empty() {
+            self.status = format!("{} does not support scenes", entry.name);
+        } else {
+            let current = entry
+                .state
+                .as_ref()
+                .ok()
+                .and_then(|state| *state.scene())
+                .and_then(|scene| scenes.iter().position(|s| *s == scene));
+            let list_state = ListState::default().with_selected(Some(current.unwrap_or(0)));
+            self.scene_picker = Some((scenes, list_state));
+        }
+    }
+
+    /// Applies a change to the selected device, reporting the outcome in the status line.
+    fn change(
+        &mut self,
+        description: &str,
+        change: impl FnOnce(SetPilotBuilder) -> Result<SetPilotBuilder, DeviceError>,
+    ) {
+        let Some(entry) = self.entries.get_mut(self.selected) else {
+            return;
+        };
+        // Changing a device consumes it, so a fresh connection is used to keep the entry intact
+        // when the change fails
+        let result = Device::connect_with(&self.connection_builder, *entry.device.ip())
+            .and_then(|device| change(device.set_pilot()))
+            .and_then(SetPilotBuilder::send);
+        match result {
+            Ok(device) => {
+                entry.device = device;
+                entry.refresh();
+                self.status = format!("{} {}", description, entry.name);
+            }
+            Err(e) => self.status = format!("Could not change {}: {}", entry.name, one_line(&e)),
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [table_area, status_area, help_area] = Layout::vertical([
+            Constraint::Min(0),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let mut rows = Vec::new();
+        let mut selected_row = None;
+        let mut room = None;
+        for (i, entry) in self.entries.iter().enumerate() {
+            let room_id = entry.device.room_id();
+            if self.by_room && room != Some(room_id) {
+                let heading = match room_id {
+                    0 => "No room".to_string(),
+                    id => format!("Room {}", id),
+                };
+                rows.push(Row::new(vec![Cell::from(heading).bold()]));
+                room = Some(room_id);
+            }
+            if i == self.selected {
+                selected_row = Some(rows.len());
+            }
+            rows.push(entry_row(entry));
+        }
+
+        let widths = [
+            Constraint::Length(17),
+            Constraint::Length(15),
+            Constraint::Fill(1),
+            Constraint::Length(5),
+            Constraint::Length(10),
+            Constraint::Fill(1),
+            Constraint::Length(6),
+        ];
+        let header = Row::new(vec![
+            "Name",
+            "IP",
+            "Type",
+            "Power",
+            "Brightness",
+            "Color",
+            "Signal",
+        ])
+        .bold();
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(Block::bordered().title(format!(" wizctl: {} devices ", self.entries.len())))
+            .row_highlight_style(Style::new().reversed());
+        let mut table_state = TableState::default().with_selected(selected_row);
+        frame.render_stateful_widget(table, table_area, &mut table_state);
+
+        frame.render_widget(Line::from(self.status.as_str()), status_area);
+        frame.render_widget(Line::from(HELP).dim(), help_area);
+
+        if let Some((scenes, list_state)) = &self.scene_picker {
+            let height = (scenes.len() as u16 + 2).min(table_area.height);
+            let area = centered(table_area, 24, height);
+            let list = List::new(scenes.iter().map(Scene::name))
+                .block(Block::bordered().title(" Scene "))
+                .highlight_style(Style::new().reversed());
+            frame.render_widget(Clear, area);
+            frame.render_stateful_widget(list, area, &mut list_state.clone());
+        }
+    }
+}
+
+fn entry_row(entry: &Entry) -> Row<'static> {
+    let device = &entry.device;
+    let mut cells = vec![
+        entry.name.clone(),
+        device.ip().to_string(),
+        device.fetch_model_name(),
+    ];
+    match &entry.state {
+        Ok(state) => cells.extend([
+            if *state.on() { "on" } else { "off" }.to_string(),
+            state
+                .brightness()
+                .map(|brightness| format!("{}%", brightness))
+                .unwrap_or_default(),
+            match (state.scene(), state.color()) {
+                (Some(scene), _) => scene.to_string(),
+                (None, Some(color)) => color.to_string(),
+                (None, None) => String::new(),
+            },
+            rssi_to_signal_strength(*state.rssi()),
+        ]),
+        Err(e) => cells.extend([
+            String::new(),
+            String::new(),
+            format!("Unreachable: {}", e),
+            String::new(),
+        ]),
+    }
+    Row::new(cells)
+}
+
+/// A rectangle of the given size in the middle of `area`.
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let [area] = Layout::horizontal([Constraint::Length(width)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [area] = Layout::vertical([Constraint::Length(height)])
+        .flex(Flex::Center)
+        .areas(area);
+    area
+}
+
+/// Joins the lines of an error message so that it fits in the status line.
+fn one_line(error: &impl ToString) -> String {
+    error.to_string().replace('\n', " ")
+}
+
+#[derive(Debug, Error)]
+pub enum TuiError {
+    #[error("Could not draw to the terminal!\n{0}")]
+    TerminalError(#[from] io::Error),
+    #[error("{0}")]
+    DeviceError(#[from] DeviceError),
+}
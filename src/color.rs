@@ -1,8 +1,103 @@
+mod names;
+
 use derive_getters::Getters;
 use std::{fmt::Display, str::FromStr};
 use thiserror::Error;
 
-#[derive(Clone, Debug, Getters)]
+use crate::connection::messages::get_model_config::GetModelConfigResponseResult;
+
+/// A color in any of the forms that a device can be set to or described with. Each form is kept as
+/// given, so converting into a `Color` and back is lossless.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Color {
+    Rgbcw(RGBCW),
+    Rgb(Rgb),
+    Hsv(Hsv),
+    Kelvin(u16),
+}
+
+impl Color {
+    /// Converts the color into the RGB color it appears as, using `white_channels` to account for
+    /// the light of the white LEDs in RGBCW values.
+    pub fn to_rgb(&self, white_channels: &WhiteChannels) -> Rgb {
+        match self {
+            Self::Rgbcw(rgbcw) => rgbcw_to_rgb(rgbcw, white_channels),
+            Self::Rgb(rgb) => *rgb,
+            Self::Hsv(hsv) => (*hsv).into(),
+            Self::Kelvin(kelvin) => kelvin_to_rgb(*kelvin),
+        }
+    }
+
+    pub fn to_hsv(&self, white_channels: &WhiteChannels) -> Hsv {
+        match self {
+            Self::Hsv(hsv) => *hsv,
+            _ => self.to_rgb(white_channels).into(),
+        }
+    }
+
+    /// Converts the color into the temperature of the nearest white.
+    pub fn to_kelvin(&self, white_channels: &WhiteChannels) -> u16 {
+        match self {
+            Self::Kelvin(kelvin) => *kelvin,
+            _ => rgb_to_nearest_kelvin(&self.to_rgb(white_channels)),
+        }
+    }
+}
+
+impl Display for Color {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Rgbcw(rgbcw) => rgbcw.fmt(f),
+            Self::Rgb(rgb) => rgb.fmt(f),
+            Self::Hsv(hsv) => hsv.fmt(f),
+            Self::Kelvin(kelvin) => write!(f, "{}K", kelvin),
+        }
+    }
+}
+
+/// Parses a temperature such as `2700K`, an RGBCW value such as `255,250,245,0,0`, an HSV color, or
+/// anything else that [`Rgb`] can be parsed from.
+impl FromStr for Color {
+    type Err = ColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = s.trim();
+        // Names such as "pink" also end in k, so only numbers are temperatures
+        let kelvin = value
+            .strip_suffix('K')
+            .or_else(|| value.strip_suffix('k'))
+            .and_then(|kelvin| kelvin.trim().parse().ok());
+        if let Some(kelvin) = kelvin {
+            Ok(Self::Kelvin(kelvin))
+        } else if value.contains(',') && !value.contains('(') {
+            value.parse().map(Self::Rgbcw)
+        } else if value.starts_with("hsv(") {
+            value.parse().map(Self::Hsv)
+        } else {
+            value.parse().map(Self::Rgb)
+        }
+    }
+}
+
+impl From<RGBCW> for Color {
+    fn from(value: RGBCW) -> Self {
+        Self::Rgbcw(value)
+    }
+}
+
+impl From<Rgb> for Color {
+    fn from(value: Rgb) -> Self {
+        Self::Rgb(value)
+    }
+}
+
+impl From<Hsv> for Color {
+    fn from(value: Hsv) -> Self {
+        Self::Hsv(value)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Getters)]
 pub struct RGBCW {
     r: u8,
     g: u8,
@@ -11,6 +106,12 @@ pub struct RGBCW {
     w: u8,
 }
 
+impl RGBCW {
+    pub fn new(r: u8, g: u8, b: u8, c: u8, w: u8) -> Self {
+        Self { r, g, b, c, w }
+    }
+}
+
 impl Display for RGBCW {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -44,11 +145,627 @@ impl FromStr for RGBCW {
             c: *values.get(3).ok_or(ColorError::ParseError(s.to_string()))?,
             w: *values.get(4).ok_or(ColorError::ParseError(s.to_string()))?,
         })
+        .and_then(|rgbcw| reject_black(rgbcw, s, |rgbcw| rgbcw == &Self::default()))
+    }
+}
+
+/// WiZ firmware only uses the ratio between the color channels and sets the brightness separately
+/// with `dimming`, so the color is scaled to full value and the white channels are left off.
+impl From<Rgb> for RGBCW {
+    fn from(value: Rgb) -> Self {
+        let max = value.r.max(value.g).max(value.b);
+        if max == 0 {
+            return Self::new(0, 0, 0, 0, 0);
+        }
+        let scale = |channel: u8| (u16::from(channel) * 255 / u16::from(max)) as u8;
+        Self::new(scale(value.r), scale(value.g), scale(value.b), 0, 0)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Getters)]
+pub struct Rgb {
+    r: u8,
+    g: u8,
+    b: u8,
+}
+
+impl Rgb {
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+}
+
+impl Display for Rgb {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+/// Parses CSS hex (`#ff8800` or `#f80`), `rgb(255,136,0)`, `hsv(32,100%,100%)` or a CSS/X11 color
+/// name (e.g. `orange`). Black is rejected, since it has no hue to scale up to full value.
+impl FromStr for Rgb {
+    type Err = ColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ColorError::InvalidColor(s.to_string());
+        let value = s.trim();
+
+        let rgb = if let Some(hex) = value.strip_prefix('#') {
+            parse_hex(hex).ok_or_else(error)
+        } else if let Some(args) = parse_function(value, "rgb") {
+            match args
+                .iter()
+                .map(|arg| arg.parse::<u8>())
+                .collect::<Result<Vec<u8>, _>>()
+                .map_err(|_| error())?[..]
+            {
+                [r, g, b] => Ok(Self::new(r, g, b)),
+                _ => Err(error()),
+            }
+        } else if value.starts_with("hsv(") {
+            Ok(value.parse::<Hsv>()?.into())
+        } else {
+            names::lookup(value)
+                .or_else(|| parse_hex(value))
+                .ok_or_else(error)
+        }?;
+        reject_black(rgb, s, |rgb| rgb.r == 0 && rgb.g == 0 && rgb.b == 0)
+    }
+}
+
+impl From<Hsv> for Rgb {
+    fn from(value: Hsv) -> Self {
+        let chroma = value.v * value.s;
+        let sector = value.h / 60.0;
+        let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+        let (r, g, b) = match sector as u8 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let m = value.v - chroma;
+        let channel = |c: f32| ((c + m) * 255.0).round() as u8;
+        Self::new(channel(r), channel(g), channel(b))
+    }
+}
+
+/// A color as hue in degrees, and saturation and value between 0 and 1.
+#[derive(Clone, Copy, Debug, PartialEq, Getters)]
+pub struct Hsv {
+    h: f32,
+    s: f32,
+    v: f32,
+}
+
+impl Hsv {
+    /// Creates a color from its components, wrapping the hue into `[0, 360)` and clamping the
+    /// saturation and value into `[0, 1]`.
+    pub fn new(h: f32, s: f32, v: f32) -> Self {
+        Self {
+            h: h.rem_euclid(360.0),
+            s: s.clamp(0.0, 1.0),
+            v: v.clamp(0.0, 1.0),
+        }
+    }
+}
+
+impl Display for Hsv {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "hsv({},{}%,{}%)",
+            self.h.round(),
+            (self.s * 100.0).round(),
+            (self.v * 100.0).round()
+        )
+    }
+}
+
+/// Parses `hsv(h,s%,v%)`, where the hue is in degrees and the saturation and value are percentages.
+/// A value of 0 is black and is rejected.
+impl FromStr for Hsv {
+    type Err = ColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ColorError::InvalidColor(s.to_string());
+        let args = parse_function(s.trim(), "hsv").ok_or_else(error)?;
+        let [h, sat, v] = args[..] else {
+            return Err(error());
+        };
+
+        let h = h
+            .trim_end_matches("deg")
+            .parse::<f32>()
+            .map_err(|_| error())?;
+        let percentage = |arg: &str| {
+            arg.trim_end_matches('%')
+                .parse::<f32>()
+                .ok()
+                .filter(|value| (0.0..=100.0).contains(value))
+                .map(|value| value / 100.0)
+                .ok_or_else(error)
+        };
+        reject_black(Self::new(h, percentage(sat)?, percentage(v)?), s, |hsv| {
+            hsv.v == 0.0
+        })
     }
 }
 
+impl From<Rgb> for Hsv {
+    fn from(value: Rgb) -> Self {
+        let r = f32::from(value.r) / 255.0;
+        let g = f32::from(value.g) / 255.0;
+        let b = f32::from(value.b) / 255.0;
+        let max = r.max(g).max(b);
+        let chroma = max - r.min(g).min(b);
+
+        let h = if chroma == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / chroma)
+        } else if max == g {
+            60.0 * ((b - r) / chroma + 2.0)
+        } else {
+            60.0 * ((r - g) / chroma + 4.0)
+        };
+        let s = if max == 0.0 { 0.0 } else { chroma / max };
+        Self::new(h, s, max)
+    }
+}
+
+/// Color temperatures of the white LEDs and the relative output of each channel of a device, used
+/// to split the white component of a color out into the `c` and `w` channels.
+#[derive(Clone, Debug, Getters)]
+pub struct WhiteChannels {
+    warm_kelvin: u16,
+    cool_kelvin: u16,
+    /// Relative output of the r, g, b, c and w channels, where 255 is full output.
+    render_factor: [u8; 5],
+}
+
+impl Default for WhiteChannels {
+    fn default() -> Self {
+        Self {
+            warm_kelvin: 2700,
+            cool_kelvin: 6500,
+            render_factor: [255; 5],
+        }
+    }
+}
+
+/// Uses the inner `cct_range` bounds, since the outer ones are reached by mixing in the red LED,
+/// and the first five entries of `render_factor`, which correspond to the r, g, b, c and w
+/// channels. Zeros are treated as unknown and replaced with the defaults.
+impl From<&GetModelConfigResponseResult> for WhiteChannels {
+    fn from(value: &GetModelConfigResponseResult) -> Self {
+        let default = Self::default();
+        let cct_range = value.cct_range();
+        let mut render_factor = default.render_factor;
+        for (factor, reported) in render_factor.iter_mut().zip(value.render_factor()) {
+            if *reported != 0 {
+                *factor = *reported;
+            }
+        }
+        Self {
+            warm_kelvin: Some(cct_range[1])
+                .filter(|kelvin| *kelvin != 0)
+                .unwrap_or(default.warm_kelvin),
+            cool_kelvin: Some(cct_range[2])
+                .filter(|kelvin| *kelvin != 0)
+                .unwrap_or(default.cool_kelvin),
+            render_factor,
+        }
+    }
+}
+
+impl WhiteChannels {
+    fn gains(&self) -> [f32; 5] {
+        self.render_factor.map(|factor| f32::from(factor) / 255.0)
+    }
+}
+
+/// Range of temperatures that the blackbody approximation is valid for.
+const MIN_KELVIN: u16 = 1000;
+const MAX_KELVIN: u16 = 40000;
+
+/// Number of mixes between the warm and cool white LEDs that are tried when extracting white.
+const WHITE_MIX_STEPS: u16 = 64;
+
+/// Converts an RGB color into RGBCW by moving as much of it as possible onto the white LEDs.
+///
+/// The white component is the mix of the warm and cool LEDs with the most luminance that fits under
+/// the color, with the LEDs modelled as blackbodies at the temperatures in `white_channels`. The remainder is
+/// sent to the RGB LEDs, each channel is compensated by its render factor, and the result is
+/// scaled to full value since brightness is set separately with `dimming`.
+pub fn rgb_to_rgbcw(rgb: &Rgb, white_channels: &WhiteChannels) -> RGBCW {
+    let target = [rgb.r, rgb.g, rgb.b].map(|channel| f32::from(channel) / 255.0);
+    let warm = blackbody(white_channels.warm_kelvin);
+    let cool = blackbody(white_channels.cool_kelvin);
+
+    let (white, cool_fraction, _) = (0..=WHITE_MIX_STEPS)
+        .map(|step| {
+            let cool_fraction = f32::from(step) / f32::from(WHITE_MIX_STEPS);
+            let mix = [0, 1, 2].map(|i| lerp(warm[i], cool[i], cool_fraction));
+            let white = (0..3)
+                .map(|i| target[i] / mix[i])
+                .fold(f32::INFINITY, f32::min);
+            (white, cool_fraction, white * luminance(mix))
+        })
+        .fold((0.0, 0.0, 0.0), |best, candidate| {
+            if candidate.2 > best.2 {
+                candidate
+            } else {
+                best
+            }
+        });
+
+    let mut channels = [0.0; 5];
+    for i in 0..3 {
+        channels[i] = (target[i] - white * lerp(warm[i], cool[i], cool_fraction)).max(0.0);
+    }
+    channels[3] = white * cool_fraction;
+    channels[4] = white * (1.0 - cool_fraction);
+
+    for (channel, gain) in channels.iter_mut().zip(white_channels.gains()) {
+        *channel /= gain;
+    }
+    let [r, g, b, c, w] = to_full_value(channels);
+    RGBCW::new(r, g, b, c, w)
+}
+
+/// Converts an RGBCW value into the RGB color it appears as, by adding the light of the white LEDs
+/// to the RGB channels. This is the reverse of [`rgb_to_rgbcw`], up to the scaling to full value.
+pub fn rgbcw_to_rgb(rgbcw: &RGBCW, white_channels: &WhiteChannels) -> Rgb {
+    let mut channels = [rgbcw.r, rgbcw.g, rgbcw.b, rgbcw.c, rgbcw.w].map(|c| f32::from(c) / 255.0);
+    for (channel, gain) in channels.iter_mut().zip(white_channels.gains()) {
+        *channel *= gain;
+    }
+    let warm = blackbody(white_channels.warm_kelvin);
+    let cool = blackbody(white_channels.cool_kelvin);
+
+    let mut rgb = [0.0; 5];
+    for i in 0..3 {
+        rgb[i] = channels[i] + channels[3] * cool[i] + channels[4] * warm[i];
+    }
+    let [r, g, b, _, _] = to_full_value(rgb);
+    Rgb::new(r, g, b)
+}
+
+/// Approximates the color of white light at a temperature, scaled to full value.
+pub fn kelvin_to_rgb(kelvin: u16) -> Rgb {
+    let [r, g, b] = blackbody(kelvin).map(|channel| (channel * 255.0).round() as u8);
+    Rgb::new(r, g, b)
+}
+
+/// Finds the temperature, to the nearest 10K, whose white is closest in hue and saturation to
+/// `rgb`. The brightness of `rgb` is ignored.
+pub fn rgb_to_nearest_kelvin(rgb: &Rgb) -> u16 {
+    let max = f32::from(rgb.r.max(rgb.g).max(rgb.b).max(1));
+    let target = [rgb.r, rgb.g, rgb.b].map(|channel| f32::from(channel) / max);
+    (MIN_KELVIN..=MAX_KELVIN)
+        .step_by(10)
+        .map(|kelvin| {
+            let white = blackbody(kelvin);
+            let white_max = white.into_iter().fold(0.0, f32::max);
+            let distance: f32 = (0..3)
+                .map(|i| (white[i] / white_max - target[i]).powi(2))
+                .sum();
+            (kelvin, distance)
+        })
+        .fold((MIN_KELVIN, f32::INFINITY), |best, candidate| {
+            if candidate.1 < best.1 {
+                candidate
+            } else {
+                best
+            }
+        })
+        .0
+}
+
+/// Scales channels between 0 and 1 so that the largest one is 255.
+fn to_full_value(channels: [f32; 5]) -> [u8; 5] {
+    let max = channels.into_iter().fold(0.0, f32::max);
+    if max <= 0.0 {
+        return [0; 5];
+    }
+    channels.map(|channel| (channel / max * 255.0).round() as u8)
+}
+
+/// Relative luminance of linear RGB, using the Rec. 709 coefficients.
+fn luminance(rgb: [f32; 3]) -> f32 {
+    0.2126 * rgb[0] + 0.7152 * rgb[1] + 0.0722 * rgb[2]
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+/// Approximates the color of a blackbody at `kelvin` as RGB between 0 and 1, using Tanner
+/// Helland's fit of the CIE 1964 color matching functions.
+fn blackbody(kelvin: u16) -> [f32; 3] {
+    let temperature = f32::from(kelvin.clamp(MIN_KELVIN, MAX_KELVIN)) / 100.0;
+
+    let r = if temperature <= 66.0 {
+        255.0
+    } else {
+        329.69873 * (temperature - 60.0).powf(-0.13320476)
+    };
+    let g = if temperature <= 66.0 {
+        99.4708 * temperature.ln() - 161.11957
+    } else {
+        288.12216 * (temperature - 60.0).powf(-0.075514846)
+    };
+    let b = if temperature >= 66.0 {
+        255.0
+    } else if temperature <= 19.0 {
+        0.0
+    } else {
+        138.51773 * (temperature - 10.0).ln() - 305.0448
+    };
+
+    [r, g, b].map(|channel| channel.clamp(0.0, 255.0) / 255.0)
+}
+
+fn parse_hex(hex: &str) -> Option<Rgb> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |digits: &str| u8::from_str_radix(digits, 16).ok();
+    match hex.len() {
+        3 => {
+            let digits = hex.as_bytes();
+            let channel = |digit: u8| channel(&(digit as char).to_string()).map(|c| c * 17);
+            Some(Rgb::new(
+                channel(digits[0])?,
+                channel(digits[1])?,
+                channel(digits[2])?,
+            ))
+        }
+        6 => Some(Rgb::new(
+            channel(&hex[0..2])?,
+            channel(&hex[2..4])?,
+            channel(&hex[4..6])?,
+        )),
+        _ => None,
+    }
+}
+
+/// Fails with [`ColorError::Black`] if `is_black` holds for a color parsed from `s`, since an
+/// all-zero color can only be shown by turning the light off.
+fn reject_black<T>(color: T, s: &str, is_black: impl Fn(&T) -> bool) -> Result<T, ColorError> {
+    if is_black(&color) {
+        Err(ColorError::Black(s.to_string()))
+    } else {
+        Ok(color)
+    }
+}
+
+/// Splits a CSS-style function call such as `rgb(1, 2, 3)` into its trimmed arguments.
+fn parse_function<'a>(value: &'a str, name: &str) -> Option<Vec<&'a str>> {
+    value
+        .strip_prefix(name)?
+        .trim_start()
+        .strip_prefix('(')?
+        .strip_suffix(')')
+        .map(|args| args.split(',').map(str::trim).collect())
+}
+
 #[derive(Debug, Error)]
 pub enum ColorError {
     #[error("Could not parse RGBCW values from \"{0}\"!")]
     ParseError(String),
+    #[error("Could not parse a color from \"{0}\"!")]
+    InvalidColor(String),
+    #[error("\"{0}\" is black, turn the light off instead!")]
+    Black(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_kelvin() {
+        assert_eq!("2700k".parse::<Color>().unwrap(), Color::Kelvin(2700));
+        assert_eq!("2700K".parse::<Color>().unwrap(), Color::Kelvin(2700));
+    }
+
+    #[test]
+    fn parses_names_ending_in_k() {
+        assert_eq!(
+            "pink".parse::<Color>().unwrap(),
+            Color::Rgb(Rgb::new(255, 192, 203))
+        );
+        assert_eq!(
+            "hotpink".parse::<Color>().unwrap(),
+            Color::Rgb(Rgb::new(255, 105, 180))
+        );
+    }
+
+    #[test]
+    fn rejects_black() {
+        for black in [
+            "black",
+            "#000",
+            "rgb(0,0,0)",
+            "hsv(120,100%,0%)",
+            "0,0,0,0,0",
+        ] {
+            assert!(
+                matches!(black.parse::<Color>(), Err(ColorError::Black(_))),
+                "{black}"
+            );
+        }
+        assert!(matches!(
+            "#000000".parse::<Rgb>(),
+            Err(ColorError::Black(_))
+        ));
+    }
+
+    #[test]
+    fn parses_names_with_separators() {
+        let khaki = Rgb::new(189, 183, 107);
+        for name in ["darkkhaki", "Dark Khaki", "dark-khaki", "DARK_KHAKI"] {
+            assert_eq!(name.parse::<Rgb>().unwrap(), khaki, "{name}");
+        }
+    }
+
+    #[test]
+    fn parses_hex() {
+        assert_eq!("#ff8800".parse::<Rgb>().unwrap(), Rgb::new(255, 136, 0));
+        assert_eq!("#F80".parse::<Rgb>().unwrap(), Rgb::new(255, 136, 0));
+        assert_eq!("ff8800".parse::<Rgb>().unwrap(), Rgb::new(255, 136, 0));
+        for invalid in ["#ff880", "#ff88001", "#gg8800", "#", "#ff 800"] {
+            assert!(invalid.parse::<Rgb>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn parses_rgb() {
+        assert_eq!(
+            "rgb(255,136,0)".parse::<Rgb>().unwrap(),
+            Rgb::new(255, 136, 0)
+        );
+        assert_eq!(
+            " rgb ( 1, 2, 3 ) ".parse::<Rgb>().unwrap(),
+            Rgb::new(1, 2, 3)
+        );
+        for invalid in [
+            "rgb(256,0,0)",
+            "rgb(1,2)",
+            "rgb(1,2,3,4)",
+            "rgb(-1,0,0)",
+            "rgb(1,2,3",
+        ] {
+            assert!(invalid.parse::<Rgb>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn parses_hsv() {
+        assert_eq!(
+            "hsv(32,100%,100%)".parse::<Hsv>().unwrap(),
+            Hsv::new(32.0, 1.0, 1.0)
+        );
+        assert_eq!(
+            "hsv(-90deg, 50%, 25%)".parse::<Hsv>().unwrap(),
+            Hsv::new(270.0, 0.5, 0.25)
+        );
+        assert_eq!(
+            "hsv(120,100%,100%)".parse::<Rgb>().unwrap(),
+            Rgb::new(0, 255, 0)
+        );
+        for invalid in [
+            "hsv(0,101%,100%)",
+            "hsv(0,50%)",
+            "hsv(red,50%,50%)",
+            "hsv(0,-1%,0%)",
+        ] {
+            assert!(invalid.parse::<Hsv>().is_err(), "{invalid}");
+            assert!(invalid.parse::<Rgb>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn scales_rgb_to_full_value() {
+        let grey: Rgb = "#808080".parse().unwrap();
+        assert_eq!(RGBCW::from(grey), RGBCW::new(255, 255, 255, 0, 0));
+        assert_eq!(
+            RGBCW::from(Rgb::new(0, 64, 128)),
+            RGBCW::new(0, 127, 255, 0, 0)
+        );
+    }
+
+    #[test]
+    fn round_trips_rgbcw() {
+        let white_channels = WhiteChannels::default();
+        for rgb in [
+            Rgb::new(255, 0, 0),
+            Rgb::new(0, 255, 0),
+            Rgb::new(0, 0, 255),
+            Rgb::new(255, 136, 0),
+            Rgb::new(255, 255, 255),
+            Rgb::new(128, 128, 128),
+            Rgb::new(200, 100, 150),
+        ] {
+            let rgbcw = rgb_to_rgbcw(&rgb, &white_channels);
+            let round_tripped = rgbcw_to_rgb(&rgbcw, &white_channels);
+            let expected = RGBCW::from(rgb);
+            for (actual, expected) in [
+                (round_tripped.r, expected.r),
+                (round_tripped.g, expected.g),
+                (round_tripped.b, expected.b),
+            ] {
+                assert!(
+                    actual.abs_diff(expected) <= 2,
+                    "{rgb} became {rgbcw} and then {round_tripped}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn converts_kelvin_to_rgb() {
+        assert_eq!(kelvin_to_rgb(6600), Rgb::new(255, 255, 255));
+        let warm = kelvin_to_rgb(2700);
+        assert_eq!(warm.r, 255);
+        assert!(warm.g > warm.b, "{warm}");
+        let cool = kelvin_to_rgb(10000);
+        assert_eq!(cool.b, 255);
+        assert!(cool.r < cool.g, "{cool}");
+        assert_eq!(kelvin_to_rgb(0), kelvin_to_rgb(MIN_KELVIN));
+        assert_eq!(kelvin_to_rgb(u16::MAX), kelvin_to_rgb(MAX_KELVIN));
+    }
+
+    #[test]
+    fn finds_the_nearest_kelvin() {
+        for kelvin in [1500, 2200, 2700, 4000, 6500] {
+            let nearest = rgb_to_nearest_kelvin(&kelvin_to_rgb(kelvin));
+            assert!(
+                nearest.abs_diff(kelvin) <= 50,
+                "{kelvin}K became {nearest}K"
+            );
+        }
+        // Brightness is ignored
+        let dim = Hsv::from(kelvin_to_rgb(2700));
+        let dim = Rgb::from(Hsv::new(dim.h, dim.s, dim.v / 2.0));
+        assert!(rgb_to_nearest_kelvin(&dim).abs_diff(2700) <= 100);
+        assert!(rgb_to_nearest_kelvin(&Rgb::new(0, 0, 0)) >= MIN_KELVIN);
+    }
+
+    #[test]
+    fn converts_between_forms() {
+        let white_channels = WhiteChannels::default();
+        let orange = Color::Rgb(Rgb::new(255, 136, 0));
+        assert_eq!(orange.to_rgb(&white_channels), Rgb::new(255, 136, 0));
+        let hsv = orange.to_hsv(&white_channels);
+        assert!((hsv.h - 32.0).abs() < 0.5, "{hsv}");
+        assert_eq!((hsv.s, hsv.v), (1.0, 1.0));
+        assert_eq!(Rgb::from(hsv), Rgb::new(255, 136, 0));
+
+        let hsv = Color::Hsv(Hsv::new(240.0, 1.0, 1.0));
+        assert_eq!(hsv.to_rgb(&white_channels), Rgb::new(0, 0, 255));
+        assert_eq!(hsv.to_hsv(&white_channels), Hsv::new(240.0, 1.0, 1.0));
+
+        let kelvin = Color::Kelvin(2700);
+        assert_eq!(kelvin.to_kelvin(&white_channels), 2700);
+        assert_eq!(kelvin.to_rgb(&white_channels), kelvin_to_rgb(2700));
+
+        // The warm LED on its own is the warm white temperature
+        let warm = Color::Rgbcw(RGBCW::new(0, 0, 0, 0, 255));
+        assert!(warm.to_kelvin(&white_channels).abs_diff(2700) <= 50);
+        let cool = Color::Rgbcw(RGBCW::new(0, 0, 0, 255, 0));
+        assert!(cool.to_kelvin(&white_channels).abs_diff(6500) <= 50);
+    }
+
+    #[test]
+    fn moves_white_onto_the_white_channels() {
+        let rgbcw = rgb_to_rgbcw(&Rgb::new(255, 255, 255), &WhiteChannels::default());
+        assert!(rgbcw.c + rgbcw.w > 0, "{rgbcw}");
+        let rgbcw = rgb_to_rgbcw(&Rgb::new(255, 0, 0), &WhiteChannels::default());
+        assert_eq!(rgbcw, RGBCW::new(255, 0, 0, 0, 0));
+    }
 }
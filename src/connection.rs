@@ -1,105 +1,452 @@
+mod interfaces;
 pub mod messages;
 mod network;
+mod telemetry;
 
 use std::io;
 //use crate::{color::RGBCW, devices::Device};
 use core::str;
+use derive_getters::Getters;
+pub use interfaces::NetworkInterface;
 use messages::{
     error::ErrorResponse,
     get_model_config::{GetModelConfigRequest, GetModelConfigResponse},
     get_pilot::{GetPilotRequest, GetPilotResponse},
     get_power::{GetPowerRequest, GetPowerResponse},
     get_system_config::{GetSystemConfigRequest, GetSystemConfigResponse},
+    get_user_config::{GetUserConfigRequest, GetUserConfigResponse},
+    pulse::{PulseRequest, PulseResponse},
+    raw::RawRequest,
+    reboot::{RebootRequest, RebootResponse},
+    reset::{ResetRequest, ResetResponse},
     set_pilot::{SetPilotRequest, SetPilotResponse},
+    set_user_config::{SetUserConfigRequest, SetUserConfigResponse},
+    set_wifi_config::{SetWifiConfigRequest, SetWifiConfigResponse},
     SetResponse,
 };
 use network::send_and_receive_datagram;
-use network::NetworkError;
 use network::{broadcast_and_receive_datagrams, init_socket};
-use serde::{de::DeserializeOwned, Serialize};
-use std::net::{IpAddr, UdpSocket};
+use network::{Datagram, NetworkError};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6, UdpSocket};
+use std::time::{Duration, Instant};
+use telemetry::{DiscoverySpan, RequestSpan};
 use thiserror::Error;
 
-const PORT: u16 = 38899;
+/// Port that WiZ devices listen on for UDP requests.
+pub const DEFAULT_PORT: u16 = 38899;
+/// How long to wait for a device to respond to a request.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(2);
+/// How long to collect responses to a discovery broadcast.
+pub const DEFAULT_DISCOVERY_WINDOW: Duration = Duration::from_secs(2);
 
 pub struct Connection {
     socket: UdpSocket,
+    port: u16,
+    broadcast_addresses: Vec<SocketAddr>,
+    interface_names: Vec<String>,
+    timeout: Duration,
+    retries: u32,
+    discovery_window: Duration,
 }
 
 impl Connection {
     pub fn new() -> Result<Self, io::Error> {
-        Ok(Self {
-            socket: init_socket()?,
+        ConnectionBuilder::new().build()
+    }
+}
+
+/// Configures the port, bind address, interfaces, broadcast targets and timing of a [`Connection`].
+///
+/// By default, requests are sent to port 38899 from an ephemeral port on all IPv4 interfaces, and
+/// discovery broadcasts on the directed broadcast address of every local interface. Binding to an
+/// IPv6 address discovers over IPv6 instead, using the all-nodes multicast group on each interface.
+/// Requests time out after 2 seconds without being retried.
+#[derive(Clone, Debug)]
+pub struct ConnectionBuilder {
+    port: u16,
+    /// Unspecified IPv4 address if `None`, unless chosen for a device by
+    /// [`ConnectionBuilder::for_device`].
+    bind_address: Option<SocketAddr>,
+    broadcast_addresses: Vec<DeviceAddress>,
+    interface_names: Vec<String>,
+    timeout: Duration,
+    retries: u32,
+    discovery_window: Duration,
+}
+
+impl Default for ConnectionBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConnectionBuilder {
+    pub fn new() -> Self {
+        Self {
+            port: DEFAULT_PORT,
+            bind_address: None,
+            broadcast_addresses: Vec::new(),
+            interface_names: Vec::new(),
+            timeout: DEFAULT_TIMEOUT,
+            retries: 0,
+            discovery_window: DEFAULT_DISCOVERY_WINDOW,
+        }
+    }
+
+    pub fn build(self) -> Result<Connection, io::Error> {
+        Ok(Connection {
+            socket: init_socket(
+                self.bind_address
+                    .unwrap_or_else(|| SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0).into()),
+            )?,
+            broadcast_addresses: self
+                .broadcast_addresses
+                .iter()
+                .map(|address| address.with_default_port(self.port))
+                .collect(),
+            port: self.port,
+            interface_names: self.interface_names,
+            timeout: self.timeout,
+            retries: self.retries,
+            discovery_window: self.discovery_window,
         })
     }
+
+    /// Sets the port that requests are sent to, unless an address specifies its own port.
+    pub fn port(mut self, value: u16) -> Self {
+        self.port = value;
+        self
+    }
+
+    /// Sets the local address that the connection's socket is bound to.
+    pub fn bind_address(mut self, value: impl Into<SocketAddr>) -> Self {
+        self.bind_address = Some(value.into());
+        self
+    }
+
+    /// Prepares the connection for requests to a single device, using the port of `address` if it
+    /// has one, and binding to an IPv6 address if the device has one and no bind address is set.
+    pub fn for_device(mut self, address: &DeviceAddress) -> Self {
+        if let DeviceAddress::Socket(socket_address) = address {
+            self.port = socket_address.port();
+        }
+        if self.bind_address.is_none() && address.ip().is_ipv6() {
+            self.bind_address = Some(SocketAddrV6::new(Ipv6Addr::UNSPECIFIED, 0, 0, 0).into());
+        }
+        self
+    }
+
+    /// Adds a target for discovery broadcasts, replacing the broadcast addresses of the local
+    /// interfaces.
+    pub fn broadcast_address(mut self, value: impl Into<DeviceAddress>) -> Self {
+        self.broadcast_addresses.push(value.into());
+        self
+    }
+
+    /// Restricts discovery to the interface with the given name (e.g. `eth0`). Can be called
+    /// multiple times to discover on several interfaces.
+    pub fn interface(mut self, name: impl Into<String>) -> Self {
+        self.interface_names.push(name.into());
+        self
+    }
+
+    /// Sets how long to wait for a device to respond to a request.
+    pub fn timeout(mut self, value: Duration) -> Self {
+        self.timeout = value;
+        self
+    }
+
+    /// Sets how many times a request is sent again when a device does not respond in time.
+    /// Requests that cannot safely be repeated, such as `reboot` or `setWifiConfig`, are never
+    /// retried, since the device may have handled a request whose response was lost.
+    pub fn retries(mut self, value: u32) -> Self {
+        self.retries = value;
+        self
+    }
+
+    /// Sets how long to collect responses to a discovery broadcast. Devices that respond later are
+    /// missed.
+    pub fn discovery_window(mut self, value: Duration) -> Self {
+        self.discovery_window = value;
+        self
+    }
+
+    /// Lengthens the discovery window to at least `value`.
+    pub(crate) fn min_discovery_window(mut self, value: Duration) -> Self {
+        self.discovery_window = self.discovery_window.max(value);
+        self
+    }
+}
+
+/// Address of a device, either as a bare IP that uses the connection's port, or a full socket
+/// address for devices listening on a non-standard port.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeviceAddress {
+    Ip(IpAddr),
+    Socket(SocketAddr),
+}
+
+impl DeviceAddress {
+    pub fn ip(&self) -> IpAddr {
+        match self {
+            Self::Ip(ip) => *ip,
+            Self::Socket(socket_address) => socket_address.ip(),
+        }
+    }
+
+    pub fn with_default_port(&self, port: u16) -> SocketAddr {
+        match self {
+            Self::Ip(ip) => SocketAddr::new(*ip, port),
+            Self::Socket(socket_address) => *socket_address,
+        }
+    }
+}
+
+impl From<IpAddr> for DeviceAddress {
+    fn from(value: IpAddr) -> Self {
+        Self::Ip(value)
+    }
+}
+
+impl From<Ipv4Addr> for DeviceAddress {
+    fn from(value: Ipv4Addr) -> Self {
+        Self::Ip(value.into())
+    }
+}
+
+impl From<Ipv6Addr> for DeviceAddress {
+    fn from(value: Ipv6Addr) -> Self {
+        Self::Ip(value.into())
+    }
+}
+
+impl From<SocketAddr> for DeviceAddress {
+    fn from(value: SocketAddr) -> Self {
+        Self::Socket(value)
+    }
 }
 
 impl Connection {
     // TODO: Need more reliable discovery for lights that are off
-    pub fn discover(&self) -> Result<Vec<(IpAddr, GetSystemConfigResponse)>, ConnectionError> {
+    /// Broadcasts a request for the system config of all devices, returning each response or the
+    /// reason it could not be read.
+    pub fn discover(
+        &self,
+    ) -> Result<Vec<Result<DiscoveryResponse, ConnectionError>>, ConnectionError> {
+        let interfaces = self.discovery_interfaces()?;
+        let broadcast_addresses = if !self.broadcast_addresses.is_empty() {
+            self.broadcast_addresses.clone()
+        } else if !interfaces.is_empty() {
+            let mut broadcast_addresses = interfaces
+                .iter()
+                .map(|interface| interface.broadcast_address(self.port))
+                .collect::<Vec<SocketAddr>>();
+            broadcast_addresses.sort();
+            broadcast_addresses.dedup();
+            broadcast_addresses
+        } else {
+            vec![SocketAddr::new(Ipv4Addr::BROADCAST.into(), self.port)]
+        };
+
+        let span = DiscoverySpan::enter(&broadcast_addresses, self.discovery_window);
         let broadcast_data = serde_json::to_vec(&GetSystemConfigRequest::default())?;
-        Ok(
-            broadcast_and_receive_datagrams(&self.socket, &broadcast_data, PORT)?
-                .into_iter()
-                .map(|datagram| {
-                    serde_json::from_slice::<GetSystemConfigResponse>(datagram.data())
-                        .map(|system_config| (datagram.source_address().ip(), system_config))
-                })
-                .collect::<Result<Vec<(IpAddr, GetSystemConfigResponse)>, _>>()?,
+        let datagrams = broadcast_and_receive_datagrams(
+            &self.socket,
+            &broadcast_data,
+            &broadcast_addresses,
+            self.discovery_window,
         )
+        .map_err(|e| {
+            let error = ConnectionError::from(e);
+            span.error(&error);
+            error
+        })?;
+        span.responses(datagrams.len());
+        Ok(datagrams
+            .into_iter()
+            .map(|datagram| {
+                let address = *datagram.source_address();
+                log::debug!(
+                    "Received from {}: {}",
+                    address,
+                    String::from_utf8_lossy(datagram.data())
+                );
+                serde_json::from_slice::<GetSystemConfigResponse>(datagram.data())
+                    .map(|system_config| DiscoveryResponse {
+                        address,
+                        interface: interfaces
+                            .iter()
+                            .find(|interface| interface.contains(&address))
+                            .map(|interface| interface.name().to_string()),
+                        system_config,
+                    })
+                    .map_err(|source| ConnectionError::InvalidDiscoveryResponse { address, source })
+            })
+            .collect())
+    }
+
+    /// Local interfaces to discover on, limited to the requested names and the address family of
+    /// the socket.
+    fn discovery_interfaces(&self) -> Result<Vec<NetworkInterface>, ConnectionError> {
+        let is_ipv4 = self
+            .socket
+            .local_addr()
+            .map_err(ConnectionError::InterfaceError)?
+            .is_ipv4();
+        let interfaces: Vec<NetworkInterface> = NetworkInterface::list()
+            .map_err(ConnectionError::InterfaceError)?
+            .into_iter()
+            .filter(|interface| interface.ip().is_ipv4() == is_ipv4)
+            .filter(|interface| {
+                self.interface_names.is_empty() || self.interface_names.contains(interface.name())
+            })
+            .collect();
+        if !self.interface_names.is_empty() && interfaces.is_empty() {
+            return Err(ConnectionError::UnknownInterfaces(
+                self.interface_names.clone(),
+            ));
+        }
+        Ok(interfaces)
     }
 
     pub fn get_system_config(
         &self,
-        ip: &IpAddr,
+        address: impl Into<DeviceAddress>,
     ) -> Result<GetSystemConfigResponse, ConnectionError> {
         let request = GetSystemConfigRequest::default();
-        self.send_get_request::<GetSystemConfigRequest, GetSystemConfigResponse>(ip, &request)
-    }
-
-    //pub fn get_power(&self, ip: &IpAddr) -> Result<u32> {
-    //    let request = GetPowerRequest::default();
-    //    Ok(*self
-    //        .send_get_request::<GetPowerRequest, GetPowerResponse>(ip, &request)
-    //        .map_err(|e| {
-    //            if let Some(ConnectionError::ReceivedErrorResponse { ip, code, message }) =
-    //                e.downcast_ref::<ConnectionError>()
-    //            {
-    //                if *code == -32601 && message == "Method not found" {
-    //                    return ConnectionError::DeviceDoesNotSupportGetPower(*ip).into();
-    //                }
-    //            };
-    //            e
-    //        })?
-    //        .result()
-    //        .power())
-    //}
-
-    pub fn get_pilot(&self, ip: &IpAddr) -> Result<GetPilotResponse, ConnectionError> {
+        self.send_get_request::<GetSystemConfigRequest, GetSystemConfigResponse>(
+            address.into(),
+            &request,
+        )
+    }
+
+    pub fn get_model_config(
+        &self,
+        address: impl Into<DeviceAddress>,
+    ) -> Result<GetModelConfigResponse, ConnectionError> {
+        let request = GetModelConfigRequest::default();
+        self.send_get_request::<GetModelConfigRequest, GetModelConfigResponse>(
+            address.into(),
+            &request,
+        )
+    }
+
+    pub fn get_power(
+        &self,
+        address: impl Into<DeviceAddress>,
+    ) -> Result<GetPowerResponse, ConnectionError> {
+        let request = GetPowerRequest::default();
+        self.send_get_request::<GetPowerRequest, GetPowerResponse>(address.into(), &request)
+    }
+
+    pub fn get_pilot(
+        &self,
+        address: impl Into<DeviceAddress>,
+    ) -> Result<GetPilotResponse, ConnectionError> {
         let request = GetPilotRequest::default();
-        self.send_get_request::<GetPilotRequest, GetPilotResponse>(ip, &request)
+        self.send_get_request::<GetPilotRequest, GetPilotResponse>(address.into(), &request)
+    }
+
+    pub fn set_pilot(
+        &self,
+        address: impl Into<DeviceAddress>,
+        request: SetPilotRequest,
+    ) -> Result<(), ConnectionError> {
+        self.send_set_request::<SetPilotRequest, SetPilotResponse>(address.into(), &request)
+    }
+
+    pub fn get_user_config(
+        &self,
+        address: impl Into<DeviceAddress>,
+    ) -> Result<GetUserConfigResponse, ConnectionError> {
+        let request = GetUserConfigRequest::default();
+        self.send_get_request::<GetUserConfigRequest, GetUserConfigResponse>(
+            address.into(),
+            &request,
+        )
+    }
+
+    pub fn set_user_config(
+        &self,
+        address: impl Into<DeviceAddress>,
+        request: SetUserConfigRequest,
+    ) -> Result<(), ConnectionError> {
+        self.send_set_request::<SetUserConfigRequest, SetUserConfigResponse>(
+            address.into(),
+            &request,
+        )
+    }
+
+    pub fn set_wifi_config(
+        &self,
+        address: impl Into<DeviceAddress>,
+        request: SetWifiConfigRequest,
+    ) -> Result<(), ConnectionError> {
+        self.send_set_request::<SetWifiConfigRequest, SetWifiConfigResponse>(
+            address.into(),
+            &request,
+        )
     }
 
-    pub fn set_pilot(&self, ip: &IpAddr, request: SetPilotRequest) -> Result<(), ConnectionError> {
-        self.send_set_request::<SetPilotRequest, SetPilotResponse>(ip, &request)
+    pub fn pulse(
+        &self,
+        address: impl Into<DeviceAddress>,
+        request: PulseRequest,
+    ) -> Result<(), ConnectionError> {
+        self.send_set_request::<PulseRequest, PulseResponse>(address.into(), &request)
+    }
+
+    pub fn reboot(&self, address: impl Into<DeviceAddress>) -> Result<(), ConnectionError> {
+        let request = RebootRequest::default();
+        self.send_set_request::<RebootRequest, RebootResponse>(address.into(), &request)
+    }
+
+    /// Erases all settings of the device, including its Wi-Fi configuration.
+    pub fn reset(&self, address: impl Into<DeviceAddress>) -> Result<(), ConnectionError> {
+        let request = ResetRequest::default();
+        self.send_set_request::<ResetRequest, ResetResponse>(address.into(), &request)
+    }
+
+    /// Sends a request for any method with the given params, returning the response as JSON. Error
+    /// responses from the device are returned as [`ConnectionError::ErrorResponse`].
+    pub fn call_raw(
+        &self,
+        address: impl Into<DeviceAddress>,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, ConnectionError> {
+        let request = RawRequest::new(method, params);
+        self.send_request_and_receive_response::<RawRequest, serde_json::Value>(
+            address.into(),
+            &request,
+        )
     }
 }
 
 impl Connection {
-    fn send_get_request<T, U>(&self, ip: &IpAddr, request: &T) -> Result<U, ConnectionError>
+    fn send_get_request<T, U>(
+        &self,
+        address: DeviceAddress,
+        request: &T,
+    ) -> Result<U, ConnectionError>
     where
         T: Serialize,
         U: DeserializeOwned,
     {
-        self.send_request_and_receive_response::<T, U>(ip, request)
+        self.send_request_and_receive_response::<T, U>(address, request)
     }
 
-    fn send_set_request<T, U>(&self, ip: &IpAddr, request: &T) -> Result<(), ConnectionError>
+    fn send_set_request<T, U>(
+        &self,
+        address: DeviceAddress,
+        request: &T,
+    ) -> Result<(), ConnectionError>
     where
         T: Serialize,
         U: DeserializeOwned + SetResponse + 'static,
     {
-        let response = self.send_request_and_receive_response::<T, U>(ip, request)?;
+        let response = self.send_request_and_receive_response::<T, U>(address, request)?;
         if response.success() {
             Ok(())
         } else {
@@ -109,7 +456,7 @@ impl Connection {
 
     fn send_request_and_receive_response<T, U>(
         &self,
-        ip: &IpAddr,
+        address: DeviceAddress,
         request: &T,
     ) -> Result<U, ConnectionError>
     where
@@ -117,8 +464,75 @@ impl Connection {
         U: DeserializeOwned,
     {
         let send_data = serde_json::to_vec(request).expect("failed to serialize request");
+        let method = method_of(&send_data).unwrap_or_else(|| "unknown".to_string());
+        let address = address.with_default_port(self.port);
+        let span = RequestSpan::enter(&address.ip(), &method, send_data.len());
+        let result = self.exchange(&address, &method, &send_data, &span);
+        if let Err(e) = &result {
+            span.error(e);
+        }
+        result
+    }
 
-        let datagram = send_and_receive_datagram(&self.socket, &send_data, ip, PORT)?;
+    /// Sends a serialized request, retrying if the device does not respond in time, and parses
+    /// the response.
+    fn exchange<U>(
+        &self,
+        address: &SocketAddr,
+        method: &str,
+        send_data: &[u8],
+        span: &RequestSpan,
+    ) -> Result<U, ConnectionError>
+    where
+        U: DeserializeOwned,
+    {
+        let retries = if NON_IDEMPOTENT_METHODS.contains(&method) {
+            0
+        } else {
+            self.retries
+        };
+        let mut attempt = 0;
+        let datagram = loop {
+            span.attempt(attempt + 1);
+            log::debug!("Sending {} to {}: {}", method, address, Redacted(send_data));
+            let start = Instant::now();
+            // Replies name the method they answer, so that a late reply to an earlier request is
+            // not taken for this one
+            let is_reply = |datagram: &Datagram| {
+                method_of(datagram.data()).is_none_or(|reply_method| reply_method == method)
+            };
+            match send_and_receive_datagram(
+                &self.socket,
+                send_data,
+                address,
+                self.timeout,
+                is_reply,
+            ) {
+                Ok(datagram) => {
+                    span.response(start.elapsed(), datagram.data().len());
+                    log::debug!(
+                        "Received {} bytes from {} after {:?}",
+                        datagram.data().len(),
+                        address,
+                        start.elapsed()
+                    );
+                    log::debug!("Response from {}: {}", address, Redacted(datagram.data()));
+                    break datagram;
+                }
+                Err(NetworkError::NoUdpResponse(timeout)) if attempt < retries => {
+                    span.retry(timeout);
+                    attempt += 1;
+                    log::info!(
+                        "No response from {} after {:?}, retrying ({}/{})",
+                        address,
+                        timeout,
+                        attempt,
+                        retries
+                    );
+                }
+                Err(e) => return Err(e.into()),
+            }
+        };
         let response_json = str::from_utf8(datagram.data())?;
         if let Ok(error_response) = serde_json::from_str::<ErrorResponse>(response_json) {
             Err(ConnectionError::ErrorResponse {
@@ -131,6 +545,64 @@ impl Connection {
     }
 }
 
+/// Methods that are not retried, since repeating them after the device handled the first request
+/// restarts it again, or repeats a change whose effect is not absolute.
+const NON_IDEMPOTENT_METHODS: &[&str] =
+    &["reboot", "reset", "setWifiConfig", "setUserConfig", "pulse"];
+
+/// Params whose values are kept out of logs, such as the Wi-Fi passphrase of `setWifiConfig`.
+const SECRET_PARAMS: &[&str] = &["psk", "password", "passphrase"];
+
+/// Formats a serialized request or response for logging as it was sent or received, unless the
+/// values of [`SECRET_PARAMS`] have to be replaced. Only formatted if the log message is enabled.
+struct Redacted<'a>(&'a [u8]);
+
+impl std::fmt::Display for Redacted<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let verbatim = String::from_utf8_lossy(self.0);
+        let Ok(mut message) = serde_json::from_slice::<serde_json::Value>(self.0) else {
+            return write!(f, "{}", verbatim);
+        };
+        let mut redacted = false;
+        for field in ["params", "result"] {
+            let Some(values) = message.get_mut(field).and_then(|v| v.as_object_mut()) else {
+                continue;
+            };
+            for (key, value) in values {
+                if SECRET_PARAMS.contains(&key.as_str()) {
+                    *value = "<redacted>".into();
+                    redacted = true;
+                }
+            }
+        }
+        if redacted {
+            write!(f, "{}", message)
+        } else {
+            write!(f, "{}", verbatim)
+        }
+    }
+}
+
+/// Reads the `method` of a serialized request or response, if it has one.
+fn method_of(data: &[u8]) -> Option<String> {
+    #[derive(Deserialize)]
+    struct Method {
+        method: String,
+    }
+    serde_json::from_slice::<Method>(data)
+        .ok()
+        .map(|message| message.method)
+}
+
+/// A device that responded to a discovery broadcast.
+#[derive(Debug, Getters)]
+pub struct DiscoveryResponse {
+    address: SocketAddr,
+    /// Name of the local interface on whose subnet the device was found, if known.
+    interface: Option<String>,
+    system_config: GetSystemConfigResponse,
+}
+
 #[derive(Debug, Error)]
 pub enum ConnectionError {
     #[error("Received response with invalid UTF8!\n{0}")]
@@ -143,4 +615,84 @@ pub enum ConnectionError {
     InvalidResponse(#[from] serde_json::Error),
     #[error("Device was not able to handle request!\n{0:?}")]
     UnsuccessfulRequest(Box<dyn SetResponse>),
+    #[error("Could not deserialize discovery response from {address}!\n{source}")]
+    InvalidDiscoveryResponse {
+        address: SocketAddr,
+        source: serde_json::Error,
+    },
+    #[error("Could not list network interfaces!\n{0}")]
+    InterfaceError(#[source] io::Error),
+    #[error("None of the requested network interfaces were found: {0:?}!")]
+    UnknownInterfaces(Vec<String>),
+}
+
+impl ConnectionError {
+    /// Short, stable name of the kind of error, for logs and traces.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::InvalidUtf8(_) => "invalid_utf8",
+            Self::NetworkError(NetworkError::NoUdpResponse(_)) => "timeout",
+            Self::NetworkError(_) => "network",
+            Self::ErrorResponse { .. } => "error_response",
+            Self::InvalidResponse(_) => "invalid_response",
+            Self::UnsuccessfulRequest(_) => "unsuccessful",
+            Self::InvalidDiscoveryResponse { .. } => "invalid_discovery_response",
+            Self::InterfaceError(_) => "interface",
+            Self::UnknownInterfaces(_) => "unknown_interfaces",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_secrets_from_logs() {
+        let request = serde_json::to_vec(&SetWifiConfigRequest::new("home", "hunter22")).unwrap();
+        let logged = Redacted(&request).to_string();
+        assert!(!logged.contains("hunter22"), "{logged}");
+        assert!(logged.contains(r#""psk":"<redacted>""#), "{logged}");
+        assert!(logged.contains(r#""ssid":"home""#), "{logged}");
+
+        let raw = br#"{"method":"setPilot","params":{"state":true,"dimming":50}}"#;
+        assert_eq!(
+            Redacted(raw).to_string(),
+            r#"{"method":"setPilot","params":{"state":true,"dimming":50}}"#
+        );
+        assert_eq!(Redacted(b"not json").to_string(), "not json");
+    }
+
+    /// Sends `method` to a device that never responds, returning how many times it was sent.
+    fn attempts(method: &str) -> usize {
+        let device = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let connection = ConnectionBuilder::new()
+            .timeout(Duration::from_millis(50))
+            .retries(2)
+            .build()
+            .unwrap();
+        let result = connection.call_raw(
+            device.local_addr().unwrap(),
+            method,
+            serde_json::Value::Null,
+        );
+        assert!(matches!(
+            result,
+            Err(ConnectionError::NetworkError(NetworkError::NoUdpResponse(
+                _
+            )))
+        ));
+        device.set_nonblocking(true).unwrap();
+        let mut buf = [0; 512];
+        std::iter::from_fn(|| device.recv(&mut buf).ok()).count()
+    }
+
+    #[test]
+    fn retries_only_idempotent_methods() {
+        assert_eq!(attempts("getPilot"), 3);
+        assert_eq!(attempts("setPilot"), 3);
+        for method in NON_IDEMPOTENT_METHODS {
+            assert_eq!(attempts(method), 1, "{method}");
+        }
+    }
 }
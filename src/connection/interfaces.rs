@@ -0,0 +1,86 @@
+use derive_getters::Getters;
+use if_addrs::{get_if_addrs, IfAddr};
+use std::{
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV6},
+};
+
+/// Link-local multicast group that all IPv6 nodes listen on, used in place of a broadcast.
+const IPV6_ALL_NODES: Ipv6Addr = Ipv6Addr::new(0xff02, 0, 0, 0, 0, 0, 0, 1);
+
+/// An address assigned to a local network interface.
+#[derive(Clone, Debug, Getters)]
+pub struct NetworkInterface {
+    name: String,
+    ip: IpAddr,
+    prefix_length: u8,
+    broadcast: Option<Ipv4Addr>,
+    index: Option<u32>,
+}
+
+impl NetworkInterface {
+    /// Lists the addresses of all local interfaces, excluding loopback.
+    pub fn list() -> Result<Vec<Self>, io::Error> {
+        Ok(get_if_addrs()?
+            .into_iter()
+            .filter(|interface| !interface.is_loopback())
+            .map(|interface| {
+                let (prefix_length, broadcast) = match &interface.addr {
+                    IfAddr::V4(addr) => (addr.prefixlen, addr.broadcast),
+                    IfAddr::V6(addr) => (addr.prefixlen, None),
+                };
+                Self {
+                    ip: interface.ip(),
+                    name: interface.name,
+                    prefix_length,
+                    broadcast,
+                    index: interface.index,
+                }
+            })
+            .collect())
+    }
+
+    /// Returns whether `address` is reachable on the same link as this interface. Scoped IPv6
+    /// addresses are matched by interface index, and all others by subnet.
+    pub fn contains(&self, address: &SocketAddr) -> bool {
+        if let SocketAddr::V6(address) = address {
+            if address.scope_id() != 0 {
+                return self.index == Some(address.scope_id());
+            }
+        }
+        match (self.ip, address.ip()) {
+            (IpAddr::V4(own), IpAddr::V4(other)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - u32::from(self.prefix_length))
+                    .unwrap_or(0);
+                u32::from(own) & mask == u32::from(other) & mask
+            }
+            (IpAddr::V6(own), IpAddr::V6(other)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - u32::from(self.prefix_length))
+                    .unwrap_or(0);
+                u128::from(own) & mask == u128::from(other) & mask
+            }
+            _ => false,
+        }
+    }
+
+    /// Address to send discovery requests to on this interface. IPv4 interfaces use their
+    /// directed broadcast address, and IPv6 interfaces use the all-nodes multicast group.
+    pub fn broadcast_address(&self, port: u16) -> SocketAddr {
+        match self.ip {
+            IpAddr::V4(ip) => {
+                let broadcast = self.broadcast.unwrap_or_else(|| {
+                    let host_mask = u32::MAX
+                        .checked_shr(u32::from(self.prefix_length))
+                        .unwrap_or(0);
+                    Ipv4Addr::from(u32::from(ip) | host_mask)
+                });
+                SocketAddr::new(broadcast.into(), port)
+            }
+            IpAddr::V6(_) => {
+                SocketAddrV6::new(IPV6_ALL_NODES, port, 0, self.index.unwrap_or(0)).into()
+            }
+        }
+    }
+}
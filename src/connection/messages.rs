@@ -3,7 +3,14 @@ pub mod get_model_config;
 pub mod get_pilot;
 pub mod get_power;
 pub mod get_system_config;
+pub mod get_user_config;
+pub mod pulse;
+pub mod raw;
+pub mod reboot;
+pub mod reset;
 pub mod set_pilot;
+pub mod set_user_config;
+pub mod set_wifi_config;
 
 pub trait SetResponse: std::fmt::Debug {
     fn success(&self) -> bool;
@@ -1,3 +1,4 @@
+use derive_getters::Getters;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize)]
@@ -14,7 +15,7 @@ impl Default for GetModelConfigRequest {
 }
 
 #[allow(dead_code)]
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Getters)]
 pub struct GetModelConfigResponse {
     method: String,
     env: String,
@@ -22,7 +23,7 @@ pub struct GetModelConfigResponse {
 }
 
 #[allow(dead_code)]
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Getters)]
 pub struct GetModelConfigResponseResult {
     ps: u8,
     #[serde(alias = "pwmFreq")]
@@ -29,11 +29,21 @@ pub struct GetPilotResponseResult {
     rssi: i8,
     state: bool,
     #[serde(alias = "sceneId")]
-    scene_id: i8,
+    scene_id: u16,
     r: Option<u8>,
     g: Option<u8>,
     b: Option<u8>,
     c: Option<u8>,
     w: Option<u8>,
+    temp: Option<u16>,
     dimming: Option<u8>,
+    ratio: Option<u8>,
+    #[serde(alias = "fanState")]
+    fan_state: Option<u8>,
+    #[serde(alias = "fanSpeed")]
+    fan_speed: Option<u8>,
+    #[serde(alias = "fanMode")]
+    fan_mode: Option<u8>,
+    #[serde(alias = "fanRevrs")]
+    fan_revrs: Option<u8>,
 }
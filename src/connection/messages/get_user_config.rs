@@ -0,0 +1,45 @@
+use derive_getters::Getters;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize)]
+pub struct GetUserConfigRequest {
+    method: String,
+}
+
+impl Default for GetUserConfigRequest {
+    fn default() -> Self {
+        Self {
+            method: "getUserConfig".to_string(),
+        }
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Deserialize, Getters)]
+pub struct GetUserConfigResponse {
+    method: String,
+    env: String,
+    result: GetUserConfigResponseResult,
+}
+
+/// User settings of a device. Which fields are present depends on the device and its firmware.
+#[derive(Clone, Debug, Deserialize, Getters)]
+pub struct GetUserConfigResponseResult {
+    #[serde(alias = "fadeIn")]
+    fade_in: Option<u32>,
+    #[serde(alias = "fadeOut")]
+    fade_out: Option<u32>,
+    #[serde(alias = "fadeNight")]
+    fade_night: Option<bool>,
+    #[serde(alias = "dftDim")]
+    dft_dim: Option<u8>,
+    #[serde(alias = "pwmRange")]
+    pwm_range: Option<[u8; 2]>,
+    #[serde(alias = "whiteRange")]
+    white_range: Option<[u16; 2]>,
+    #[serde(alias = "extRange")]
+    ext_range: Option<[u16; 2]>,
+    po: Option<bool>,
+    #[serde(alias = "minDimming")]
+    min_dimming: Option<u8>,
+}
@@ -0,0 +1,46 @@
+use derive_getters::Getters;
+use serde::{Deserialize, Serialize};
+
+use super::SetResponse;
+
+/// Briefly changes the brightness of a device and then returns it to its previous state.
+#[derive(Serialize, Debug)]
+pub struct PulseRequest {
+    method: String,
+    params: PulseRequestParams,
+}
+
+impl PulseRequest {
+    /// Creates a pulse that changes the brightness by `delta` percent for `duration` milliseconds.
+    pub fn new(delta: i8, duration: u16) -> Self {
+        Self {
+            method: "pulse".to_string(),
+            params: PulseRequestParams { delta, duration },
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct PulseRequestParams {
+    delta: i8,
+    duration: u16,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Deserialize, Getters)]
+pub struct PulseResponse {
+    method: String,
+    env: String,
+    result: PulseResponseResult,
+}
+
+impl SetResponse for PulseResponse {
+    fn success(&self) -> bool {
+        *self.result().success()
+    }
+}
+
+#[derive(Debug, Deserialize, Getters)]
+pub struct PulseResponseResult {
+    success: bool,
+}
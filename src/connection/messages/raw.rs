@@ -0,0 +1,18 @@
+use serde::Serialize;
+use serde_json::Value;
+
+/// A request for any method, including those that are not modeled by this crate.
+#[derive(Serialize, Debug)]
+pub struct RawRequest {
+    method: String,
+    params: Value,
+}
+
+impl RawRequest {
+    pub fn new(method: &str, params: Value) -> Self {
+        Self {
+            method: method.to_string(),
+            params,
+        }
+    }
+}
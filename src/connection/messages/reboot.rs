@@ -0,0 +1,35 @@
+use derive_getters::Getters;
+use serde::{Deserialize, Serialize};
+
+use super::SetResponse;
+
+#[derive(Serialize, Debug)]
+pub struct RebootRequest {
+    method: String,
+}
+
+impl Default for RebootRequest {
+    fn default() -> Self {
+        Self {
+            method: "reboot".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Getters)]
+pub struct RebootResponse {
+    method: String,
+    env: String,
+    result: RebootResponseResult,
+}
+
+impl SetResponse for RebootResponse {
+    fn success(&self) -> bool {
+        *self.result().success()
+    }
+}
+
+#[derive(Debug, Deserialize, Getters)]
+pub struct RebootResponseResult {
+    success: bool,
+}
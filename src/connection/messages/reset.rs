@@ -0,0 +1,35 @@
+use derive_getters::Getters;
+use serde::{Deserialize, Serialize};
+
+use super::SetResponse;
+
+#[derive(Serialize, Debug)]
+pub struct ResetRequest {
+    method: String,
+}
+
+impl Default for ResetRequest {
+    fn default() -> Self {
+        Self {
+            method: "reset".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Getters)]
+pub struct ResetResponse {
+    method: String,
+    env: String,
+    result: ResetResponseResult,
+}
+
+impl SetResponse for ResetResponse {
+    fn success(&self) -> bool {
+        *self.result().success()
+    }
+}
+
+#[derive(Debug, Deserialize, Getters)]
+pub struct ResetResponseResult {
+    success: bool,
+}
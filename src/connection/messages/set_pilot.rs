@@ -7,6 +7,12 @@ const METHOD: &str = "setPilot";
 
 pub struct SetPilotRequestBuilder(SetPilotRequest);
 
+impl Default for SetPilotRequestBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl SetPilotRequestBuilder {
     pub fn new() -> Self {
         Self(SetPilotRequest::default())
@@ -21,6 +27,11 @@ impl SetPilotRequestBuilder {
         self
     }
 
+    pub fn scene_id(mut self, value: u16) -> Self {
+        self.0.params.scene_id = Some(value);
+        self
+    }
+
     pub fn r(mut self, value: u8) -> Self {
         self.0.params.r = Some(value);
         self
@@ -46,10 +57,40 @@ impl SetPilotRequestBuilder {
         self
     }
 
+    pub fn temp(mut self, value: u16) -> Self {
+        self.0.params.temp = Some(value);
+        self
+    }
+
     pub fn dimming(mut self, value: u8) -> Self {
         self.0.params.dimming = Some(value.to_owned());
         self
     }
+
+    pub fn ratio(mut self, value: u8) -> Self {
+        self.0.params.ratio = Some(value);
+        self
+    }
+
+    pub fn fan_state(mut self, value: bool) -> Self {
+        self.0.params.fan_state = Some(value.into());
+        self
+    }
+
+    pub fn fan_speed(mut self, value: u8) -> Self {
+        self.0.params.fan_speed = Some(value);
+        self
+    }
+
+    pub fn fan_mode(mut self, value: u8) -> Self {
+        self.0.params.fan_mode = Some(value);
+        self
+    }
+
+    pub fn fan_revrs(mut self, value: bool) -> Self {
+        self.0.params.fan_revrs = Some(value.into());
+        self
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -71,6 +112,8 @@ impl Default for SetPilotRequest {
 struct SetPilotRequestParams {
     #[serde(skip_serializing_if = "Option::is_none")]
     state: Option<bool>,
+    #[serde(rename = "sceneId", skip_serializing_if = "Option::is_none")]
+    scene_id: Option<u16>,
     #[serde(skip_serializing_if = "Option::is_none")]
     r: Option<u8>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -82,7 +125,19 @@ struct SetPilotRequestParams {
     #[serde(skip_serializing_if = "Option::is_none")]
     w: Option<u8>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    temp: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     dimming: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ratio: Option<u8>,
+    #[serde(rename = "fanState", skip_serializing_if = "Option::is_none")]
+    fan_state: Option<u8>,
+    #[serde(rename = "fanSpeed", skip_serializing_if = "Option::is_none")]
+    fan_speed: Option<u8>,
+    #[serde(rename = "fanMode", skip_serializing_if = "Option::is_none")]
+    fan_mode: Option<u8>,
+    #[serde(rename = "fanRevrs", skip_serializing_if = "Option::is_none")]
+    fan_revrs: Option<u8>,
 }
 
 #[derive(Debug, Deserialize, Getters)]
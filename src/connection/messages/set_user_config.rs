@@ -0,0 +1,103 @@
+use derive_getters::Getters;
+use serde::{Deserialize, Serialize};
+
+use super::SetResponse;
+
+const METHOD: &str = "setUserConfig";
+
+pub struct SetUserConfigRequestBuilder(SetUserConfigRequest);
+
+impl Default for SetUserConfigRequestBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SetUserConfigRequestBuilder {
+    pub fn new() -> Self {
+        Self(SetUserConfigRequest::default())
+    }
+
+    pub fn build(self) -> SetUserConfigRequest {
+        self.0
+    }
+
+    pub fn fade_in(mut self, value: u32) -> Self {
+        self.0.params.fade_in = Some(value);
+        self
+    }
+
+    pub fn fade_out(mut self, value: u32) -> Self {
+        self.0.params.fade_out = Some(value);
+        self
+    }
+
+    pub fn fade_night(mut self, value: bool) -> Self {
+        self.0.params.fade_night = Some(value);
+        self
+    }
+
+    pub fn dft_dim(mut self, value: u8) -> Self {
+        self.0.params.dft_dim = Some(value);
+        self
+    }
+
+    pub fn po(mut self, value: bool) -> Self {
+        self.0.params.po = Some(value);
+        self
+    }
+
+    pub fn min_dimming(mut self, value: u8) -> Self {
+        self.0.params.min_dimming = Some(value);
+        self
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SetUserConfigRequest {
+    method: String,
+    params: SetUserConfigRequestParams,
+}
+
+impl Default for SetUserConfigRequest {
+    fn default() -> Self {
+        Self {
+            method: METHOD.to_string(),
+            params: SetUserConfigRequestParams::default(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+struct SetUserConfigRequestParams {
+    #[serde(rename = "fadeIn", skip_serializing_if = "Option::is_none")]
+    fade_in: Option<u32>,
+    #[serde(rename = "fadeOut", skip_serializing_if = "Option::is_none")]
+    fade_out: Option<u32>,
+    #[serde(rename = "fadeNight", skip_serializing_if = "Option::is_none")]
+    fade_night: Option<bool>,
+    #[serde(rename = "dftDim", skip_serializing_if = "Option::is_none")]
+    dft_dim: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    po: Option<bool>,
+    #[serde(rename = "minDimming", skip_serializing_if = "Option::is_none")]
+    min_dimming: Option<u8>,
+}
+
+#[derive(Debug, Deserialize, Getters)]
+pub struct SetUserConfigResponse {
+    method: String,
+    env: String,
+    result: SetUserConfigResponseResult,
+}
+
+impl SetResponse for SetUserConfigResponse {
+    fn success(&self) -> bool {
+        *self.result().success()
+    }
+}
+
+#[derive(Debug, Deserialize, Getters)]
+pub struct SetUserConfigResponseResult {
+    success: bool,
+}
@@ -0,0 +1,48 @@
+use derive_getters::Getters;
+use serde::{Deserialize, Serialize};
+
+use super::SetResponse;
+
+/// Gives a device the credentials of the Wi-Fi network to join. Not `Debug`, to keep the
+/// passphrase out of logs.
+#[derive(Serialize)]
+pub struct SetWifiConfigRequest {
+    method: String,
+    params: SetWifiConfigRequestParams,
+}
+
+impl SetWifiConfigRequest {
+    pub fn new(ssid: impl Into<String>, psk: impl Into<String>) -> Self {
+        Self {
+            method: "setWifiConfig".to_string(),
+            params: SetWifiConfigRequestParams {
+                ssid: ssid.into(),
+                psk: psk.into(),
+            },
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct SetWifiConfigRequestParams {
+    ssid: String,
+    psk: String,
+}
+
+#[derive(Debug, Deserialize, Getters)]
+pub struct SetWifiConfigResponse {
+    method: String,
+    env: String,
+    result: SetWifiConfigResponseResult,
+}
+
+impl SetResponse for SetWifiConfigResponse {
+    fn success(&self) -> bool {
+        *self.result().success()
+    }
+}
+
+#[derive(Debug, Deserialize, Getters)]
+pub struct SetWifiConfigResponseResult {
+    success: bool,
+}
@@ -1,16 +1,19 @@
 use derive_getters::Getters;
 use std::{
     io,
-    net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket},
+    net::{IpAddr, SocketAddr, UdpSocket},
     thread::sleep,
     time::{Duration, Instant},
 };
 use thiserror::Error;
 
-const MAX_WAIT: Duration = Duration::from_secs(2);
+use super::telemetry;
 
-pub fn init_socket() -> Result<UdpSocket, io::Error> {
-    let bind_address = SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0);
+/// Largest payload of a UDP datagram over IPv6 (65535 bytes less the UDP header), which is also
+/// larger than any over IPv4, so that no response is too large to receive.
+const MAX_DATAGRAM_SIZE: usize = 65527;
+
+pub fn init_socket(bind_address: SocketAddr) -> Result<UdpSocket, io::Error> {
     let socket = UdpSocket::bind(bind_address)?;
     socket.set_nonblocking(true)?;
     Ok(socket)
@@ -19,21 +22,52 @@ pub fn init_socket() -> Result<UdpSocket, io::Error> {
 pub fn broadcast_and_receive_datagrams(
     socket: &UdpSocket,
     broadcast_data: &Vec<u8>,
-    port: u16,
+    broadcast_addresses: &[SocketAddr],
+    window: Duration,
 ) -> Result<Vec<Datagram>, NetworkError> {
-    let broadcast_address = SocketAddrV4::new(Ipv4Addr::BROADCAST, port);
-    socket.set_broadcast(true)?;
-    socket.send_to(broadcast_data, broadcast_address)?;
-    socket.set_broadcast(false)?;
+    let is_ipv4 = socket.local_addr()?.is_ipv4();
+    if is_ipv4 {
+        socket.set_broadcast(true)?;
+    }
+    // An interface that cannot send, such as an unreachable VPN or container bridge, should not
+    // stop discovery on the others
+    let mut sent = 0;
+    let mut send_error = None;
+    for broadcast_address in broadcast_addresses {
+        log::debug!("Broadcasting to {}", broadcast_address);
+        match socket.send_to(broadcast_data, broadcast_address) {
+            Ok(_) => sent += 1,
+            Err(e) => {
+                log::warn!("Could not broadcast to {}: {}", broadcast_address, e);
+                send_error = Some(e);
+            }
+        }
+    }
+    if is_ipv4 {
+        socket.set_broadcast(false)?;
+    }
+    if let (0, Some(e)) = (sent, send_error) {
+        return Err(e.into());
+    }
 
-    sleep(MAX_WAIT);
+    sleep(window);
 
+    let mut buf = receive_buffer();
     let mut datagrams = Vec::new();
 
     loop {
-        match recv_from_socket(socket) {
+        match recv_from_socket(socket, &mut buf) {
             Ok(datagram) => {
                 if datagram.data() == broadcast_data {
+                    log::trace!(
+                        "Ignoring echo of broadcast from {}",
+                        datagram.source_address()
+                    );
+                    telemetry::ignored_datagram(
+                        datagram.source_address(),
+                        datagram.data().len(),
+                        "broadcast echo",
+                    );
                     continue;
                 }
 
@@ -54,29 +88,46 @@ pub fn broadcast_and_receive_datagrams(
     Ok(datagrams)
 }
 
+/// Sends a datagram and waits for the reply, skipping any datagram that `is_reply` rejects, such as
+/// a late reply to an earlier request. Datagrams that arrived before sending are discarded.
 pub fn send_and_receive_datagram(
     socket: &UdpSocket,
     send_data: &[u8],
-    ip: &IpAddr,
-    port: u16,
+    address: &SocketAddr,
+    timeout: Duration,
+    is_reply: impl Fn(&Datagram) -> bool,
 ) -> Result<Datagram, NetworkError> {
-    socket.send_to(send_data, SocketAddr::new(*ip, port))?;
+    let mut buf = receive_buffer();
+    discard_pending_datagrams(socket, &mut buf)?;
+    socket.send_to(send_data, address)?;
 
     let start = Instant::now();
     loop {
-        if start.elapsed() >= MAX_WAIT {
-            return Err(NetworkError::NoUdpResponse(MAX_WAIT));
+        if start.elapsed() >= timeout {
+            return Err(NetworkError::NoUdpResponse(timeout));
         }
 
-        let datagram_result = recv_from_socket(socket);
+        let datagram_result = recv_from_socket(socket, &mut buf);
         match datagram_result {
             Ok(datagram) => {
-                if datagram.source_address().ip() != *ip {
+                if datagram.source_address().ip() != address.ip() {
                     return Err(NetworkError::IncorrectResponseAddress {
                         actual_address: datagram.source_address().ip(),
-                        expected_address: *ip,
+                        expected_address: address.ip(),
                     });
                 }
+                if !is_reply(&datagram) {
+                    log::trace!(
+                        "Ignoring unexpected datagram from {}",
+                        datagram.source_address()
+                    );
+                    telemetry::ignored_datagram(
+                        datagram.source_address(),
+                        datagram.data().len(),
+                        "not a reply to the request",
+                    );
+                    continue;
+                }
 
                 return Ok(datagram);
             }
@@ -92,15 +143,42 @@ pub fn send_and_receive_datagram(
     }
 }
 
-fn recv_from_socket(socket: &UdpSocket) -> Result<Datagram, NetworkError> {
-    let mut buf = [0; 512];
-    let (n_bytes, source_address) = socket.recv_from(&mut buf)?;
-    if n_bytes == buf.len() {
+/// Reads and drops the datagrams waiting on the socket, such as replies that arrived after their
+/// request timed out.
+fn discard_pending_datagrams(socket: &UdpSocket, buf: &mut [u8]) -> Result<(), NetworkError> {
+    loop {
+        match recv_from_socket(socket, buf) {
+            Ok(datagram) => {
+                log::trace!(
+                    "Discarding stale datagram from {}",
+                    datagram.source_address()
+                );
+                telemetry::ignored_datagram(
+                    datagram.source_address(),
+                    datagram.data().len(),
+                    "stale",
+                );
+            }
+            Err(NetworkError::IOError(e)) if e.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Allocates a buffer for [`recv_from_socket`], one byte larger than any datagram so that a
+/// truncated one can be detected. Reused for every datagram of an exchange.
+fn receive_buffer() -> Vec<u8> {
+    vec![0; MAX_DATAGRAM_SIZE + 1]
+}
+
+/// Receives a datagram into `buf`, copying out only the bytes received.
+fn recv_from_socket(socket: &UdpSocket, buf: &mut [u8]) -> Result<Datagram, NetworkError> {
+    let (n_bytes, source_address) = socket.recv_from(buf)?;
+    if n_bytes > MAX_DATAGRAM_SIZE {
         return Err(NetworkError::BufferTooSmall(n_bytes));
     }
-    let data = buf[..n_bytes].to_vec();
     Ok(Datagram {
-        data,
+        data: buf[..n_bytes].to_vec(),
         source_address,
     })
 }
@@ -115,7 +193,7 @@ pub struct Datagram {
 pub enum NetworkError {
     #[error("{0}")]
     IOError(#[from] io::Error),
-    #[error("received UDP message was too large for buffer of size {0}")]
+    #[error("received UDP message of at least {0} bytes, larger than the {MAX_DATAGRAM_SIZE} bytes a datagram can hold")]
     BufferTooSmall(usize),
     #[error("did not receive UDP response after {0:?}")]
     NoUdpResponse(Duration),
@@ -0,0 +1,109 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
+
+use super::ConnectionError;
+
+/// Span covering a request to a device, from the first attempt until its response is parsed.
+/// Spans are only recorded with the `tracing` feature, and are empty without it.
+pub struct RequestSpan {
+    #[cfg(feature = "tracing")]
+    span: tracing::span::EnteredSpan,
+}
+
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+impl RequestSpan {
+    pub fn enter(ip: &IpAddr, method: &str, bytes_sent: usize) -> Self {
+        Self {
+            #[cfg(feature = "tracing")]
+            span: tracing::debug_span!(
+                "request",
+                ip = %ip,
+                method,
+                bytes_sent,
+                attempt = tracing::field::Empty,
+                latency_ms = tracing::field::Empty,
+                bytes_received = tracing::field::Empty,
+                error = tracing::field::Empty,
+            )
+            .entered(),
+        }
+    }
+
+    /// Records the start of an attempt, numbered from 1.
+    pub fn attempt(&self, attempt: u32) {
+        #[cfg(feature = "tracing")]
+        self.span.record("attempt", attempt);
+    }
+
+    pub fn response(&self, latency: Duration, bytes_received: usize) {
+        #[cfg(feature = "tracing")]
+        {
+            self.span
+                .record("latency_ms", latency.as_secs_f64() * 1000.0)
+                .record("bytes_received", bytes_received);
+            tracing::debug!("received response");
+        }
+    }
+
+    /// Records an attempt that timed out and is about to be retried.
+    pub fn retry(&self, timeout: Duration) {
+        #[cfg(feature = "tracing")]
+        tracing::info!(
+            timeout_ms = timeout.as_millis() as u64,
+            "no response, retrying"
+        );
+    }
+
+    pub fn error(&self, error: &ConnectionError) {
+        #[cfg(feature = "tracing")]
+        {
+            self.span.record("error", error.kind());
+            tracing::warn!(error = error.kind(), "request failed");
+        }
+    }
+}
+
+/// Span covering a discovery broadcast and the responses collected for it.
+pub struct DiscoverySpan {
+    #[cfg(feature = "tracing")]
+    span: tracing::span::EnteredSpan,
+}
+
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+impl DiscoverySpan {
+    pub fn enter(broadcast_addresses: &[SocketAddr], window: Duration) -> Self {
+        Self {
+            #[cfg(feature = "tracing")]
+            span: tracing::debug_span!(
+                "discovery",
+                broadcasts = broadcast_addresses.len(),
+                window_ms = window.as_millis() as u64,
+                responses = tracing::field::Empty,
+                error = tracing::field::Empty,
+            )
+            .entered(),
+        }
+    }
+
+    pub fn responses(&self, count: usize) {
+        #[cfg(feature = "tracing")]
+        self.span.record("responses", count);
+    }
+
+    pub fn error(&self, error: &ConnectionError) {
+        #[cfg(feature = "tracing")]
+        {
+            self.span.record("error", error.kind());
+            tracing::warn!(error = error.kind(), "discovery failed");
+        }
+    }
+}
+
+/// Records a datagram that was received but not used, such as the echo of a broadcast.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub fn ignored_datagram(source: &SocketAddr, bytes: usize, reason: &'static str) {
+    #[cfg(feature = "tracing")]
+    tracing::debug!(source = %source, bytes, reason, "ignored datagram");
+}
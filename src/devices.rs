@@ -1,63 +1,222 @@
-use std::{fmt::Display, io, net::IpAddr};
+use std::{fmt::Display, io, net::IpAddr, sync::OnceLock, thread, time::Duration};
 
-use regex::Regex;
 use thiserror::Error;
 
-use crate::connection::messages::set_pilot::SetPilotRequestBuilder;
+use crate::brightness::Brightness;
+use crate::capabilities::{Capabilities, KelvinRange};
+use crate::connection::messages::{
+    get_model_config::GetModelConfigResponseResult,
+    get_system_config::GetSystemConfigResponseResult, pulse::PulseRequest,
+    set_pilot::SetPilotRequestBuilder,
+};
+use crate::fans::{FanMode, FanState};
+use crate::products::Product;
+use crate::scenes::Scene;
+use crate::state::LightState;
+use crate::user_config::{UserConfig, UserConfigError};
 
-use super::color::RGBCW;
-use super::connection::{Connection, ConnectionError};
+use super::color::{rgb_to_rgbcw, Color, Rgb, WhiteChannels, RGBCW};
+use super::connection::{Connection, ConnectionBuilder, ConnectionError, DeviceAddress};
 
 pub struct Device {
     ip: IpAddr,
+    /// Where requests are sent, including the port and IPv6 scope if they are known.
+    address: DeviceAddress,
     mac: String,
+    interface: Option<String>,
+    module_name: String,
+    firmware_version: String,
+    room_id: usize,
+    product: Option<&'static Product>,
     kind: DeviceKind,
+    /// Looked up the first time they are needed, since it takes extra requests.
+    capabilities: OnceLock<Capabilities>,
     connection: Connection,
 }
 
 impl Device {
-    pub fn discover() -> Result<Vec<Self>, DeviceError> {
-        let connection = Connection::new().map_err(DeviceError::ClientInitError)?;
-        connection
+    /// Discovers devices on the local network. A device that responds but cannot be set up is
+    /// returned as an error in place of that device, without affecting the others.
+    pub fn discover() -> Result<Vec<Result<Self, DeviceError>>, DeviceError> {
+        Self::discover_with(&ConnectionBuilder::new())
+    }
+
+    /// Discovers devices using a connection configured by `builder`, e.g. to broadcast on a
+    /// non-standard port or to specific addresses.
+    pub fn discover_with(
+        builder: &ConnectionBuilder,
+    ) -> Result<Vec<Result<Self, DeviceError>>, DeviceError> {
+        let connection = builder
+            .clone()
+            .build()
+            .map_err(DeviceError::ClientInitError)?;
+        Ok(connection
             .discover()
             .map_err(DeviceError::ConnectError)?
             .into_iter()
-            .map(|(ip, system_config)| {
-                Ok(Self {
-                    ip,
-                    mac: system_config.result().mac().to_string(),
-                    kind: DeviceKind::from_module_name(system_config.result().module_name())?,
-                    connection: Connection::new().map_err(DeviceError::ClientInitError)?,
-                })
+            .map(|response| {
+                let response = response.map_err(DeviceError::ConnectError)?;
+                let address = DeviceAddress::from(*response.address());
+                Self::new(
+                    address,
+                    response.interface().clone(),
+                    response.system_config().result(),
+                    builder
+                        .clone()
+                        .for_device(&address)
+                        .build()
+                        .map_err(DeviceError::ClientInitError)?,
+                )
             })
-            .collect()
+            .collect())
     }
 
-    pub fn connect(ip: IpAddr) -> Result<Self, DeviceError> {
-        let connection = Connection::new().map_err(DeviceError::ClientInitError)?;
+    pub fn connect(address: impl Into<DeviceAddress>) -> Result<Self, DeviceError> {
+        Self::connect_with(&ConnectionBuilder::new(), address)
+    }
+
+    /// Connects to a device using a connection configured by `builder`. If `address` includes a
+    /// port, it takes precedence over the port of the builder, and IPv6 addresses are connected to
+    /// over IPv6 unless the builder has a bind address.
+    pub fn connect_with(
+        builder: &ConnectionBuilder,
+        address: impl Into<DeviceAddress>,
+    ) -> Result<Self, DeviceError> {
+        let address = address.into();
+        let connection = builder
+            .clone()
+            .for_device(&address)
+            .build()
+            .map_err(DeviceError::ClientInitError)?;
         let system_config = connection
-            .get_system_config(&ip)
+            .get_system_config(address)
             .map_err(DeviceError::ConnectError)?;
+        Self::new(address, None, system_config.result(), connection)
+    }
+
+    /// Identifies the product of a device from its module name. Devices with unrecognized module
+    /// names are [`DeviceKind::Unknown`].
+    fn new(
+        address: DeviceAddress,
+        interface: Option<String>,
+        system_config: &GetSystemConfigResponseResult,
+        connection: Connection,
+    ) -> Result<Self, DeviceError> {
+        let module_name = system_config.module_name();
+        let product = Product::from_module_name(module_name);
+        let kind = product
+            .map(|product| product.kind().clone())
+            .unwrap_or_else(|| DeviceKind::Unknown(module_name.to_string()));
+
         Ok(Self {
-            ip,
-            mac: system_config.result().mac().to_owned(),
-            kind: DeviceKind::from_module_name(system_config.result().module_name())?,
+            ip: address.ip(),
+            address,
+            mac: system_config.mac().to_owned(),
+            interface,
+            module_name: module_name.to_owned(),
+            firmware_version: system_config.fw_version().to_owned(),
+            room_id: *system_config.room_id(),
+            product,
+            kind,
+            capabilities: OnceLock::new(),
             connection,
         })
     }
 
+    /// Looks up the capabilities of several devices at the same time, rather than one after the
+    /// other the first time each is needed.
+    pub fn load_capabilities(devices: &[Device]) {
+        thread::scope(|scope| {
+            for device in devices {
+                scope.spawn(|| device.capabilities());
+            }
+        });
+    }
+
     pub fn ip(&self) -> &IpAddr {
         &self.ip
     }
 
+    pub fn address(&self) -> &DeviceAddress {
+        &self.address
+    }
+
     pub fn mac(&self) -> &str {
         &self.mac
     }
 
+    /// Name of the local interface the device was discovered on, if known.
+    pub fn interface(&self) -> Option<&str> {
+        self.interface.as_deref()
+    }
+
+    pub fn module_name(&self) -> &str {
+        &self.module_name
+    }
+
+    pub fn firmware_version(&self) -> &str {
+        &self.firmware_version
+    }
+
+    /// Identifier of the room the device is assigned to in the WiZ app, or 0 if it has none.
+    /// Room names are only stored in the WiZ cloud.
+    pub fn room_id(&self) -> usize {
+        self.room_id
+    }
+
+    /// Product line of the device, or `None` if its module name is not recognized.
+    pub fn product(&self) -> Option<&'static Product> {
+        self.product
+    }
+
+    /// Human-readable name of the device model, including its color temperature range if it has
+    /// tunable white (e.g. "WiZ Ceiling 2700-6500K"). The range comes from [`Self::capabilities`],
+    /// so the first call asks the device for them unless [`Self::load_capabilities`] already has.
+    pub fn fetch_model_name(&self) -> String {
+        let Some(product) = self.product else {
+            return self.kind.to_string();
+        };
+        match self.capabilities().kelvin_range() {
+            Some(range) => format!("{} {}-{}K", product.name(), range.min(), range.max()),
+            None => product.name().to_string(),
+        }
+    }
+
     pub fn kind(&self) -> &DeviceKind {
         &self.kind
     }
 
+    /// Gets what the device supports, based on its product and refined by asking it for its model
+    /// config. Dimmable devices are also asked for the minimum dimming in their user config, and
+    /// plugs whether they report power consumption. Devices that do not answer these requests keep
+    /// the capabilities of their product.
+    pub fn capabilities(&self) -> &Capabilities {
+        self.capabilities.get_or_init(|| {
+            let mut capabilities = self
+                .product
+                .map(Capabilities::from_product)
+                .unwrap_or_else(|| Capabilities::from_kind(&self.kind));
+            if let Ok(model_config) = self.connection.get_model_config(self.address) {
+                capabilities = capabilities.with_model_config(model_config.result());
+            }
+            if *capabilities.dimmable() {
+                let min_brightness = self
+                    .get_user_config()
+                    .ok()
+                    .and_then(|config| *config.min_brightness())
+                    .and_then(|percent| Brightness::new(percent).ok());
+                if let Some(min_brightness) = min_brightness {
+                    capabilities = capabilities.with_min_brightness(min_brightness);
+                }
+            }
+            if let DeviceKind::Plug = self.kind {
+                capabilities = capabilities
+                    .with_power_metering(self.connection.get_power(self.address).is_ok());
+            }
+            capabilities
+        })
+    }
+
     pub fn set_pilot(self) -> SetPilotBuilder {
         SetPilotBuilder {
             device: self,
@@ -65,17 +224,190 @@ impl Device {
         }
     }
 
+    /// Makes the device visibly pulse so that it can be found. Devices that do not support the
+    /// `pulse` method are blinked on and off instead, and then returned to their original state.
+    pub fn identify(&self) -> Result<(), DeviceError> {
+        match self.connection.pulse(
+            self.address,
+            PulseRequest::new(PULSE_DELTA, PULSE_DURATION.as_millis() as u16),
+        ) {
+            Ok(()) => Ok(()),
+            Err(ConnectionError::ErrorResponse { .. }) => self.blink(),
+            Err(e) => Err(DeviceError::ConnectError(e)),
+        }
+    }
+
+    fn blink(&self) -> Result<(), DeviceError> {
+        let was_on = *self
+            .connection
+            .get_pilot(self.address)
+            .map_err(DeviceError::ConnectError)?
+            .result()
+            .state();
+        let set_state = |state: bool| {
+            self.connection
+                .set_pilot(
+                    self.address,
+                    SetPilotRequestBuilder::new().state(state).build(),
+                )
+                .map_err(DeviceError::SetPilotError)
+        };
+
+        let mut result = Ok(());
+        for _ in 0..BLINKS {
+            result = set_state(!was_on)
+                .and_then(|_| {
+                    thread::sleep(BLINK_INTERVAL);
+                    set_state(was_on)
+                })
+                .map(|_| thread::sleep(BLINK_INTERVAL));
+            if result.is_err() {
+                break;
+            }
+        }
+        // Always try to leave the device as it was, even if blinking failed part of the way
+        result.and(set_state(was_on))
+    }
+
+    /// Restarts the device, which is unreachable for a few seconds afterwards.
+    pub fn reboot(&self) -> Result<(), DeviceError> {
+        self.connection
+            .reboot(self.address)
+            .map_err(DeviceError::ConnectError)
+    }
+
+    /// Restores the device to its factory settings. It forgets its Wi-Fi configuration and has to
+    /// be set up again, so nothing is sent unless `confirmed` is `true`.
+    pub fn factory_reset(self, confirmed: bool) -> Result<(), DeviceError> {
+        if !confirmed {
+            return Err(DeviceError::ResetNotConfirmed);
+        }
+        self.connection
+            .reset(self.address)
+            .map_err(DeviceError::ConnectError)
+    }
+
     pub fn get_rssi(&self) -> Result<i8, DeviceError> {
         Ok(self
             .connection
-            .get_pilot(&self.ip)
+            .get_pilot(self.address)
             .map_err(DeviceError::ConnectError)?
             .result()
             .rssi()
             .to_owned())
     }
+
+    /// Gets the current state of the light of the device in a single request.
+    pub fn get_state(&self) -> Result<LightState, DeviceError> {
+        Ok(LightState::from_pilot(
+            self.connection
+                .get_pilot(self.address)
+                .map_err(DeviceError::ConnectError)?
+                .result(),
+        ))
+    }
+
+    /// Gets the balance between the up and down lights of a dual-head fixture, or `None` if it does
+    /// not report one.
+    pub fn get_ratio(&self) -> Result<Option<u8>, DeviceError> {
+        Ok(*self
+            .connection
+            .get_pilot(self.address)
+            .map_err(DeviceError::ConnectError)?
+            .result()
+            .ratio())
+    }
+
+    /// Gets the state of the fan of the device, or `None` if it has no fan.
+    pub fn get_fan(&self) -> Result<Option<FanState>, DeviceError> {
+        Ok(FanState::from_pilot(
+            self.connection
+                .get_pilot(self.address)
+                .map_err(DeviceError::ConnectError)?
+                .result(),
+        ))
+    }
+
+    /// Gets the current power consumption of the device in milliwatts.
+    pub fn get_power(&self) -> Result<u32, DeviceError> {
+        if !self.capabilities().power_metering() {
+            return Err(DeviceError::UnsupportedCommand(
+                self.kind.clone(),
+                "reporting power consumption".to_string(),
+            ));
+        }
+        Ok(*self
+            .connection
+            .get_power(self.address)
+            .map_err(DeviceError::ConnectError)?
+            .result()
+            .power())
+    }
+
+    pub fn get_model_config(&self) -> Result<GetModelConfigResponseResult, DeviceError> {
+        Ok(self
+            .connection
+            .get_model_config(self.address)
+            .map_err(DeviceError::ConnectError)?
+            .result()
+            .to_owned())
+    }
+
+    /// Gets the settings of the device that persist across power cycles.
+    pub fn get_user_config(&self) -> Result<UserConfig, DeviceError> {
+        Ok(self
+            .connection
+            .get_user_config(self.address)
+            .map_err(DeviceError::ConnectError)?
+            .result()
+            .into())
+    }
+
+    /// Changes the settings that are present in `config`, leaving the others as they are.
+    pub fn set_user_config(&self, config: &UserConfig) -> Result<(), DeviceError> {
+        let request = config
+            .to_request()
+            .map_err(DeviceError::InvalidUserConfig)?;
+        self.connection
+            .set_user_config(self.address, request)
+            .map_err(DeviceError::SetUserConfigError)
+    }
+
+    /// Gets the white LED temperatures and channel render factors of the device, falling back to
+    /// defaults for devices that do not support `getModelConfig` or answer it in another shape.
+    pub fn get_white_channels(&self) -> Result<WhiteChannels, DeviceError> {
+        match self.connection.get_model_config(self.address) {
+            Ok(model_config) => Ok(model_config.result().into()),
+            Err(ConnectionError::ErrorResponse { .. } | ConnectionError::InvalidResponse(_)) => {
+                Ok(WhiteChannels::default())
+            }
+            Err(e) => Err(DeviceError::ConnectError(e)),
+        }
+    }
+
+    /// Gets the color the device is currently set to, or `None` if it is set to a scene or has no
+    /// color.
+    pub fn get_color(&self) -> Result<Option<Color>, DeviceError> {
+        Ok(self.get_state()?.color().clone())
+    }
+
+    /// Gets the color the device currently appears as, including the light of its white LEDs, for
+    /// display purposes. Returns `None` if it is set to a scene or has no color.
+    pub fn get_rgb(&self) -> Result<Option<Rgb>, DeviceError> {
+        match self.get_color()? {
+            Some(color) => Ok(Some(color.to_rgb(&self.get_white_channels()?))),
+            None => Ok(None),
+        }
+    }
 }
 
+/// Change in brightness, in percent, of the pulse used to identify a device.
+const PULSE_DELTA: i8 = -60;
+const PULSE_DURATION: Duration = Duration::from_millis(800);
+/// Number of times a device is blinked when it does not support pulsing.
+const BLINKS: usize = 3;
+const BLINK_INTERVAL: Duration = Duration::from_millis(400);
+
 pub struct SetPilotBuilder {
     device: Device,
     request_builder: SetPilotRequestBuilder,
@@ -85,7 +417,7 @@ impl SetPilotBuilder {
     pub fn send(self) -> Result<Device, DeviceError> {
         self.device
             .connection
-            .set_pilot(&self.device.ip, self.request_builder.build())
+            .set_pilot(self.device.address, self.request_builder.build())
             .map_err(DeviceError::SetPilotError)?;
         Ok(self.device)
     }
@@ -100,8 +432,19 @@ impl SetPilotBuilder {
         self
     }
 
+    /// Turns the device off if it is currently on, and on otherwise. Also returns whether the
+    /// device will be on once the request is sent.
+    pub fn toggle(self) -> Result<(Self, bool), DeviceError> {
+        let on = *self.device.get_state()?.on();
+        Ok(if on {
+            (self.off(), false)
+        } else {
+            (self.on(), true)
+        })
+    }
+
     pub fn rgbcw(mut self, value: RGBCW) -> Result<Self, DeviceError> {
-        if !self.device.kind.is_color() {
+        if !self.device.capabilities().color() {
             Err(DeviceError::UnsupportedCommand(
                 self.device.kind,
                 "setting color".to_string(),
@@ -118,24 +461,211 @@ impl SetPilotBuilder {
         }
     }
 
-    pub fn brightness(mut self, value: u8) -> Result<Self, DeviceError> {
-        if !self.device.kind.is_dimmable() {
+    /// Sets the color from an RGB value, moving its white component onto the white LEDs of the
+    /// device. Brightness is set separately.
+    pub fn rgb(self, value: Rgb) -> Result<Self, DeviceError> {
+        let white_channels = self.device.get_white_channels()?;
+        self.rgbcw(rgb_to_rgbcw(&value, &white_channels))
+    }
+
+    /// Sets the color temperature of white light.
+    pub fn kelvin(mut self, value: u16) -> Result<Self, DeviceError> {
+        match self.device.capabilities().kelvin_range() {
+            None => Err(DeviceError::UnsupportedCommand(
+                self.device.kind,
+                "setting color temperature".to_string(),
+            )),
+            Some(range) if !range.contains(value) => Err(DeviceError::KelvinOutOfRange {
+                kelvin: value,
+                range: *range,
+            }),
+            Some(_) => {
+                self.request_builder = self.request_builder.temp(value);
+                Ok(self)
+            }
+        }
+    }
+
+    pub fn scene(mut self, value: Scene) -> Result<Self, DeviceError> {
+        if !self.device.capabilities().supports_scene(&value) {
+            Err(DeviceError::UnsupportedCommand(
+                self.device.kind,
+                format!("the {} scene", value),
+            ))
+        } else {
+            self.request_builder = self.request_builder.scene_id(value.id());
+            Ok(self)
+        }
+    }
+
+    /// Sets the color from any of its forms, using the closest setting the device supports.
+    pub fn color(self, value: Color) -> Result<Self, DeviceError> {
+        match value {
+            Color::Rgbcw(rgbcw) => self.rgbcw(rgbcw),
+            Color::Rgb(rgb) => self.rgb(rgb),
+            Color::Hsv(hsv) => self.rgb(hsv.into()),
+            Color::Kelvin(kelvin) => self.kelvin(kelvin),
+        }
+    }
+
+    pub fn brightness(mut self, value: Brightness) -> Result<Self, DeviceError> {
+        let min = *self.device.capabilities().min_brightness();
+        if !self.device.capabilities().dimmable() {
             Err(DeviceError::UnsupportedCommand(
                 self.device.kind,
                 "setting brightness".to_string(),
             ))
+        } else if value < min {
+            Err(DeviceError::BrightnessBelowMinimum {
+                brightness: value,
+                min,
+            })
         } else {
-            self.request_builder = self.request_builder.dimming(value);
+            self.request_builder = self.request_builder.dimming(value.percent());
             Ok(self)
         }
     }
+
+    /// Changes the brightness by `delta` percentage points from the current brightness, clamped to
+    /// the range that the device supports.
+    pub fn brightness_by(self, delta: i32) -> Result<Self, DeviceError> {
+        if !self.device.capabilities().dimmable() {
+            return Err(DeviceError::UnsupportedCommand(
+                self.device.kind,
+                "setting brightness".to_string(),
+            ));
+        }
+        let current = self
+            .device
+            .get_state()?
+            .brightness()
+            .ok_or_else(|| DeviceError::NoCurrentValue("brightness".to_string()))?;
+        let min = *self.device.capabilities().min_brightness();
+        let value = Brightness::saturating(i32::from(current) + delta).max(min);
+        self.brightness(value)
+    }
+
+    /// Changes the color temperature by `delta` Kelvin from the current temperature, clamped to
+    /// the range that the device supports. The device must currently be set to white light.
+    pub fn kelvin_by(self, delta: i32) -> Result<Self, DeviceError> {
+        let Some(range) = *self.device.capabilities().kelvin_range() else {
+            return Err(DeviceError::UnsupportedCommand(
+                self.device.kind,
+                "setting color temperature".to_string(),
+            ));
+        };
+        let current = match self.device.get_state()?.color() {
+            Some(Color::Kelvin(kelvin)) => *kelvin,
+            _ => return Err(DeviceError::NoCurrentValue("color temperature".to_string())),
+        };
+        let value =
+            (i32::from(current) + delta).clamp(i32::from(*range.min()), i32::from(*range.max()));
+        self.kelvin(value as u16)
+    }
+
+    /// Sets the balance between the up and down lights of a dual-head fixture, as a percentage.
+    pub fn ratio(mut self, value: u8) -> Result<Self, DeviceError> {
+        if !self.device.capabilities().dual_head() {
+            Err(DeviceError::UnsupportedCommand(
+                self.device.kind,
+                "setting the up/down light ratio".to_string(),
+            ))
+        } else if value > 100 {
+            Err(DeviceError::RatioOutOfRange(value))
+        } else {
+            self.request_builder = self.request_builder.ratio(value);
+            Ok(self)
+        }
+    }
+
+    /// Returns the light to a state read earlier with [`Device::get_state`], such as to undo
+    /// temporary changes. The values came from the device, so they are sent as they are.
+    pub fn restore(mut self, state: &LightState) -> Self {
+        let mut request_builder = self.request_builder.state(*state.on());
+        if let Some(brightness) = state.brightness() {
+            request_builder = request_builder.dimming(*brightness);
+        }
+        match (state.scene(), state.color()) {
+            (Some(scene), _) => request_builder = request_builder.scene_id(scene.id()),
+            (None, Some(Color::Kelvin(kelvin))) => request_builder = request_builder.temp(*kelvin),
+            (None, Some(Color::Rgbcw(rgbcw))) => {
+                request_builder = request_builder
+                    .r(*rgbcw.r())
+                    .g(*rgbcw.g())
+                    .b(*rgbcw.b())
+                    .c(*rgbcw.c())
+                    .w(*rgbcw.w());
+            }
+            // Devices only report RGBCW and Kelvin colors
+            _ => {}
+        }
+        if let Some(ratio) = state.ratio() {
+            request_builder = request_builder.ratio(*ratio);
+        }
+        self.request_builder = request_builder;
+        self
+    }
+
+    pub fn fan_on(mut self) -> Result<Self, DeviceError> {
+        self.fan_speeds("turning on a fan")?;
+        self.request_builder = self.request_builder.fan_state(true);
+        Ok(self)
+    }
+
+    pub fn fan_off(mut self) -> Result<Self, DeviceError> {
+        self.fan_speeds("turning off a fan")?;
+        self.request_builder = self.request_builder.fan_state(false);
+        Ok(self)
+    }
+
+    /// Sets the fan speed, starting from 1 for the slowest speed.
+    pub fn fan_speed(mut self, value: u8) -> Result<Self, DeviceError> {
+        let max = self.fan_speeds("setting fan speed")?;
+        if !(1..=max).contains(&value) {
+            return Err(DeviceError::FanSpeedOutOfRange { speed: value, max });
+        }
+        self.request_builder = self.request_builder.fan_speed(value);
+        Ok(self)
+    }
+
+    /// Sets whether the fan spins in reverse, e.g. to push warm air down in winter.
+    pub fn fan_reverse(mut self, value: bool) -> Result<Self, DeviceError> {
+        self.fan_speeds("reversing a fan")?;
+        self.request_builder = self.request_builder.fan_revrs(value);
+        Ok(self)
+    }
+
+    pub fn fan_mode(mut self, value: FanMode) -> Result<Self, DeviceError> {
+        self.fan_speeds("setting fan mode")?;
+        self.request_builder = self.request_builder.fan_mode(value.id());
+        Ok(self)
+    }
+
+    /// Number of fan speeds of the device, or an error for `command` if it has no fan.
+    fn fan_speeds(&self, command: &str) -> Result<u8, DeviceError> {
+        self.device.capabilities().fan_speeds().ok_or_else(|| {
+            DeviceError::UnsupportedCommand(self.device.kind.clone(), command.to_string())
+        })
+    }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum DeviceKind {
     Plug,
     LightStrip,
     Bulb(BulbKind),
+    Filament(BulbKind),
+    Ceiling(BulbKind),
+    Downlight(BulbKind),
+    /// A fixture with separately controllable up and down lights.
+    DualHead(BulbKind),
+    Squire(BulbKind),
+    /// A ceiling fan with a light of the given kind.
+    Fan(BulbKind),
+    /// A switch or remote, which can only be turned on or off.
+    Switch,
+    /// A device with an unrecognized module name, which is sent commands on a best-effort basis.
+    Unknown(String),
 }
 
 impl Display for DeviceKind {
@@ -147,54 +677,37 @@ impl Display for DeviceKind {
                 Self::Plug => "Plug".to_string(),
                 Self::LightStrip => "Light Strip".to_string(),
                 Self::Bulb(bulb_kind) => format!("{} Bulb", bulb_kind),
+                Self::Filament(bulb_kind) => format!("{} Filament Bulb", bulb_kind),
+                Self::Ceiling(bulb_kind) => format!("{} Ceiling Light", bulb_kind),
+                Self::Downlight(bulb_kind) => format!("{} Downlight", bulb_kind),
+                Self::DualHead(bulb_kind) => format!("{} Dual Head Light", bulb_kind),
+                Self::Squire(bulb_kind) => format!("{} Squire", bulb_kind),
+                Self::Fan(bulb_kind) => format!("Fan with {} Light", bulb_kind),
+                Self::Switch => "Switch".to_string(),
+                Self::Unknown(module_name) => format!("Unknown ({})", module_name),
             }
         )
     }
 }
 
 impl DeviceKind {
-    fn from_module_name(module_name: &str) -> Result<Self, DeviceError> {
-        let identifier = Regex::new(r"^ESP\d{2}_(\w+)_\d{2}[ABIT]*$")
-            .expect("Failed to compile regex!")
-            .captures(module_name)
-            .and_then(|capture| capture.get(0))
-            .ok_or_else(|| DeviceError::UnrecognizedModuleName(module_name.to_string()))?
-            .as_str();
-
-        if identifier.contains("SOCKET") {
-            Ok(Self::Plug)
-        } else if identifier.contains("TW") {
-            Ok(Self::Bulb(BulbKind::TunableWhite))
-        } else if identifier.contains("DW") {
-            Ok(Self::Bulb(BulbKind::DimmableWhite))
-        } else if identifier.contains("RGB") {
-            if module_name.ends_with("ABI") {
-                Ok(Self::LightStrip)
-            } else {
-                Ok(Self::Bulb(BulbKind::Color))
-            }
-        } else {
-            Err(DeviceError::UnrecognizedModuleName(module_name.to_string()))
-        }
-    }
-
-    fn is_dimmable(&self) -> bool {
+    /// Kind of light that the device has, or `None` if it is not a light or is not recognized.
+    pub fn light(&self) -> Option<BulbKind> {
         match self {
-            Self::Plug => false,
-            Self::Bulb(_) | Self::LightStrip => true,
-        }
-    }
-
-    fn is_color(&self) -> bool {
-        match self {
-            Self::Plug => false,
-            Self::LightStrip => true,
-            Self::Bulb(bulb_kind) => bulb_kind.is_color(),
+            Self::Bulb(bulb_kind)
+            | Self::Filament(bulb_kind)
+            | Self::Ceiling(bulb_kind)
+            | Self::Downlight(bulb_kind)
+            | Self::DualHead(bulb_kind)
+            | Self::Squire(bulb_kind)
+            | Self::Fan(bulb_kind) => Some(*bulb_kind),
+            Self::LightStrip => Some(BulbKind::Color),
+            Self::Plug | Self::Switch | Self::Unknown(_) => None,
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum BulbKind {
     DimmableWhite,
     TunableWhite,
@@ -215,22 +728,147 @@ impl Display for BulbKind {
     }
 }
 
-impl BulbKind {
-    fn is_color(&self) -> bool {
-        matches!(self, Self::Color)
-    }
-}
-
 #[derive(Error, Debug)]
 pub enum DeviceError {
     #[error("Client failed to initialize!\n{0}")]
     ClientInitError(#[source] io::Error),
     #[error("Failed to connect to device!\n{0}")]
     ConnectError(#[source] ConnectionError),
-    #[error("Did not recognize module name: {0}!")]
-    UnrecognizedModuleName(String),
     #[error("Failed to change the state of a device!\n{0}")]
     SetPilotError(#[source] ConnectionError),
+    #[error("Failed to change the configuration of a device!\n{0}")]
+    SetUserConfigError(#[source] ConnectionError),
     #[error("{0:?} devices do not support {1}!")]
     UnsupportedCommand(DeviceKind, String),
+    #[error(
+        "Color temperature {kelvin}K is outside the range of the device ({}K to {}K)!",
+        range.min(),
+        range.max()
+    )]
+    KelvinOutOfRange { kelvin: u16, range: KelvinRange },
+    #[error("Brightness {brightness} is below the minimum of the device ({min})!")]
+    BrightnessBelowMinimum {
+        brightness: Brightness,
+        min: Brightness,
+    },
+    #[error("Ratio {0} is outside the range of 0 to 100!")]
+    RatioOutOfRange(u8),
+    #[error("Device did not report its current {0}, so it cannot be changed relative to it!")]
+    NoCurrentValue(String),
+    #[error("{0}")]
+    InvalidUserConfig(#[source] UserConfigError),
+    #[error("Factory reset was not confirmed!")]
+    ResetNotConfirmed,
+    #[error("Fan speed {speed} is outside the range of the device (1 to {max})!")]
+    FanSpeedOutOfRange { speed: u8, max: u8 },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+    use std::net::{Ipv6Addr, SocketAddr, SocketAddrV6, UdpSocket};
+
+    /// Answers requests on `bind` with `respond` until the tests end, returning the address it
+    /// listens on.
+    fn fake_device(bind: SocketAddr, respond: fn(&str) -> Value) -> SocketAddr {
+        let socket = UdpSocket::bind(bind).unwrap();
+        let address = socket.local_addr().unwrap();
+        thread::spawn(move || {
+            let mut buf = [0; 2048];
+            while let Ok((n_bytes, source)) = socket.recv_from(&mut buf) {
+                let request: Value = serde_json::from_slice(&buf[..n_bytes]).unwrap();
+                let method = request["method"].as_str().unwrap_or_default();
+                let response = json!({"method": method, "env": "pro", "result": respond(method)});
+                socket
+                    .send_to(response.to_string().as_bytes(), source)
+                    .unwrap();
+            }
+        });
+        address
+    }
+
+    fn color_bulb(method: &str) -> Value {
+        match method {
+            "getSystemConfig" => json!({
+                "mac": "a8bb50000001",
+                "homeId": 1,
+                "roomId": 2,
+                "rgn": "eu",
+                "moduleName": "ESP01_SHRGB_03",
+                "fwVersion": "1.25.0",
+                "groupId": 0,
+                "ping": 0
+            }),
+            "getPilot" => json!({
+                "mac": "a8bb50000001",
+                "rssi": -55,
+                "state": true,
+                "sceneId": 0,
+                "dimming": 50,
+                "temp": 2700
+            }),
+            _ => json!({"success": true}),
+        }
+    }
+
+    fn dimmed_bulb(method: &str) -> Value {
+        match method {
+            "getUserConfig" => json!({"fadeIn": 0, "fadeOut": 0, "minDimming": 30}),
+            _ => color_bulb(method),
+        }
+    }
+
+    #[test]
+    fn rejects_brightness_below_the_minimum_dimming() {
+        let address = fake_device((Ipv6Addr::LOCALHOST, 0).into(), dimmed_bulb);
+        let device = Device::connect(address).unwrap();
+        assert_eq!(
+            *device.capabilities().min_brightness(),
+            Brightness::new(30).unwrap()
+        );
+        assert!(matches!(
+            device.set_pilot().brightness(Brightness::new(20).unwrap()),
+            Err(DeviceError::BrightnessBelowMinimum { .. })
+        ));
+        assert!(Device::connect(address)
+            .unwrap()
+            .set_pilot()
+            .brightness(Brightness::new(30).unwrap())
+            .is_ok());
+    }
+
+    #[test]
+    fn connects_to_ipv6_devices() {
+        let address = fake_device((Ipv6Addr::LOCALHOST, 0).into(), color_bulb);
+        let device = Device::connect(address).unwrap();
+        assert!(*device.get_state().unwrap().on());
+    }
+
+    #[test]
+    fn keeps_the_scope_of_ipv6_addresses() {
+        let address = fake_device((Ipv6Addr::LOCALHOST, 0).into(), color_bulb);
+        let scoped: SocketAddr =
+            SocketAddrV6::new(Ipv6Addr::LOCALHOST, address.port(), 0, 1).into();
+        let device = Device::connect(scoped).unwrap();
+        assert_eq!(*device.address(), DeviceAddress::Socket(scoped));
+        assert!(*device.get_state().unwrap().on());
+    }
+
+    #[test]
+    fn discovers_over_ipv6() {
+        let address = fake_device((Ipv6Addr::LOCALHOST, 0).into(), color_bulb);
+        let builder = ConnectionBuilder::new()
+            .bind_address((Ipv6Addr::UNSPECIFIED, 0))
+            .broadcast_address(address)
+            .discovery_window(Duration::from_millis(200));
+        let device = Device::discover_with(&builder)
+            .unwrap()
+            .into_iter()
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(*device.address(), DeviceAddress::Socket(address));
+        assert!(*device.get_state().unwrap().on());
+    }
 }
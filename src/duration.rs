@@ -0,0 +1,54 @@
+use std::time::Duration;
+use thiserror::Error;
+
+/// Parses a duration such as `500ms`, `2s`, `1.5s` or `10m`. The unit is required, since a bare
+/// number could mean either seconds or the milliseconds that devices use.
+pub fn parse_duration(value: &str) -> Result<Duration, DurationError> {
+    let error = || DurationError::ParseError(value.to_string());
+    let i = value.find(char::is_alphabetic).ok_or_else(error)?;
+    let (number, unit) = value.split_at(i);
+    let number: f64 = number.trim().parse().map_err(|_| error())?;
+    let seconds = match unit {
+        "ms" => number / 1000.0,
+        "s" => number,
+        "m" => number * 60.0,
+        _ => return Err(error()),
+    };
+    Duration::try_from_secs_f64(seconds).map_err(|_| error())
+}
+
+#[derive(Debug, Error)]
+pub enum DurationError {
+    #[error("Expected a duration with a unit such as 500ms, 2s or 1m, got: {0}!")]
+    ParseError(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("500ms").unwrap(), Duration::from_millis(500));
+        assert_eq!(parse_duration("1.5s").unwrap(), Duration::from_millis(1500));
+        assert_eq!(parse_duration("10m").unwrap(), Duration::from_secs(600));
+        assert_eq!(parse_duration("2 s").unwrap(), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn rejects_durations_without_a_unit() {
+        for invalid in [
+            "",
+            "2",
+            "500",
+            "s",
+            "fast",
+            "5h",
+            "-1s",
+            "1.5.5s",
+            "5 seconds",
+        ] {
+            assert!(parse_duration(invalid).is_err(), "{invalid}");
+        }
+    }
+}
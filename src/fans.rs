@@ -0,0 +1,83 @@
+use derive_getters::Getters;
+use std::{fmt::Display, str::FromStr};
+use thiserror::Error;
+
+use crate::connection::messages::get_pilot::GetPilotResponseResult;
+
+/// How the fan of a fan/light combo device varies its speed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FanMode {
+    /// Runs at a constant speed.
+    Normal,
+    /// Varies the speed to imitate a natural breeze.
+    Breeze,
+}
+
+impl FanMode {
+    pub fn id(&self) -> u8 {
+        match self {
+            Self::Normal => 1,
+            Self::Breeze => 2,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            1 => Some(Self::Normal),
+            2 => Some(Self::Breeze),
+            _ => None,
+        }
+    }
+}
+
+impl Display for FanMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Normal => "normal",
+                Self::Breeze => "breeze",
+            }
+        )
+    }
+}
+
+impl FromStr for FanMode {
+    type Err = FanError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "normal" => Ok(Self::Normal),
+            "breeze" => Ok(Self::Breeze),
+            _ => Err(FanError::UnknownMode(s.to_string())),
+        }
+    }
+}
+
+/// State of the fan of a fan/light combo device, as reported by `getPilot`.
+#[derive(Clone, Debug, Getters)]
+pub struct FanState {
+    on: bool,
+    speed: Option<u8>,
+    mode: Option<FanMode>,
+    reverse: bool,
+}
+
+impl FanState {
+    /// Reads the fan fields of a `getPilot` result, or `None` if the device has no fan.
+    pub fn from_pilot(pilot: &GetPilotResponseResult) -> Option<Self> {
+        Some(Self {
+            on: (*pilot.fan_state())? != 0,
+            speed: *pilot.fan_speed(),
+            mode: pilot.fan_mode().and_then(FanMode::from_id),
+            reverse: pilot.fan_revrs().is_some_and(|reverse| reverse != 0),
+        })
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum FanError {
+    #[error("Did not recognize fan mode: {0}! Expected \"normal\" or \"breeze\"")]
+    UnknownMode(String),
+}
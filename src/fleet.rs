@@ -0,0 +1,247 @@
+use derive_getters::Getters;
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    net::IpAddr,
+};
+
+use crate::{
+    connection::ConnectionBuilder,
+    devices::{Device, DeviceError},
+};
+
+/// Signal strength in dBm below which a device is flagged as having a weak connection.
+pub const WEAK_RSSI: i8 = -70;
+
+/// An inventory of the devices on a network, grouped by module and firmware version, with any
+/// devices that need attention.
+#[derive(Clone, Debug, Serialize, Getters)]
+pub struct FleetReport {
+    devices: Vec<DeviceSummary>,
+    groups: Vec<FleetGroup>,
+    issues: Vec<FleetIssue>,
+}
+
+impl FleetReport {
+    /// Discovers devices using a connection configured by `builder` and audits them. Devices that
+    /// respond but cannot be set up are left out.
+    pub fn discover_with(builder: &ConnectionBuilder) -> Result<Self, DeviceError> {
+        let devices: Vec<Device> = Device::discover_with(builder)?
+            .into_iter()
+            .flatten()
+            .collect();
+        Ok(Self::from_devices(&devices))
+    }
+
+    /// Audits the given devices, asking each for its signal strength.
+    pub fn from_devices(devices: &[Device]) -> Self {
+        Device::load_capabilities(devices);
+        let mut devices: Vec<DeviceSummary> = devices.iter().map(DeviceSummary::new).collect();
+        devices.sort_by(|a, b| (a.ip, &a.mac).cmp(&(b.ip, &b.mac)));
+        devices.dedup_by(|a, b| a.ip == b.ip && a.mac == b.mac);
+
+        let mut groups: BTreeMap<(&str, &str), Vec<String>> = BTreeMap::new();
+        for device in &devices {
+            groups
+                .entry((&device.module_name, &device.firmware_version))
+                .or_default()
+                .push(device.mac.clone());
+        }
+
+        let mut issues = Vec::new();
+        let mut latest: BTreeMap<&str, &str> = BTreeMap::new();
+        for device in &devices {
+            latest
+                .entry(&device.module_name)
+                .and_modify(|version| {
+                    if compare_versions(&device.firmware_version, version).is_gt() {
+                        *version = &device.firmware_version;
+                    }
+                })
+                .or_insert(&device.firmware_version);
+        }
+        for device in &devices {
+            let latest = latest[device.module_name.as_str()];
+            if compare_versions(&device.firmware_version, latest).is_lt() {
+                issues.push(FleetIssue::OldFirmware {
+                    mac: device.mac.clone(),
+                    ip: device.ip,
+                    module_name: device.module_name.clone(),
+                    firmware_version: device.firmware_version.clone(),
+                    latest: latest.to_string(),
+                });
+            }
+        }
+        for device in &devices {
+            match device.rssi {
+                Some(rssi) if rssi < WEAK_RSSI => issues.push(FleetIssue::WeakSignal {
+                    mac: device.mac.clone(),
+                    ip: device.ip,
+                    rssi,
+                }),
+                None => issues.push(FleetIssue::Unresponsive {
+                    mac: device.mac.clone(),
+                    ip: device.ip,
+                }),
+                _ => {}
+            }
+        }
+        let mut macs_by_ip: BTreeMap<IpAddr, BTreeSet<&str>> = BTreeMap::new();
+        for device in &devices {
+            macs_by_ip.entry(device.ip).or_default().insert(&device.mac);
+        }
+        for (ip, macs) in macs_by_ip {
+            if macs.len() > 1 {
+                issues.push(FleetIssue::DuplicateIp {
+                    ip,
+                    macs: macs.into_iter().map(str::to_string).collect(),
+                });
+            }
+        }
+
+        let groups = groups
+            .into_iter()
+            .map(|((module_name, firmware_version), macs)| FleetGroup {
+                module_name: module_name.to_string(),
+                firmware_version: firmware_version.to_string(),
+                macs,
+            })
+            .collect();
+        Self {
+            devices,
+            groups,
+            issues,
+        }
+    }
+}
+
+/// What the audit records about a single device.
+#[derive(Clone, Debug, Serialize, Getters)]
+pub struct DeviceSummary {
+    mac: String,
+    ip: IpAddr,
+    module_name: String,
+    model_name: String,
+    firmware_version: String,
+    /// Signal strength in dBm, or `None` if the device did not respond when asked for it.
+    rssi: Option<i8>,
+}
+
+impl DeviceSummary {
+    fn new(device: &Device) -> Self {
+        Self {
+            mac: device.mac().to_string(),
+            ip: *device.ip(),
+            module_name: device.module_name().to_string(),
+            model_name: device.fetch_model_name(),
+            firmware_version: device.firmware_version().to_string(),
+            rssi: device.get_rssi().ok(),
+        }
+    }
+}
+
+/// Devices that share a module name and firmware version.
+#[derive(Clone, Debug, Serialize, Getters)]
+pub struct FleetGroup {
+    module_name: String,
+    firmware_version: String,
+    macs: Vec<String>,
+}
+
+/// Something about a device that needs attention.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "issue", rename_all = "snake_case")]
+pub enum FleetIssue {
+    /// The device runs older firmware than another device of the same module.
+    OldFirmware {
+        mac: String,
+        ip: IpAddr,
+        module_name: String,
+        firmware_version: String,
+        latest: String,
+    },
+    /// The device has a signal weaker than [`WEAK_RSSI`].
+    WeakSignal { mac: String, ip: IpAddr, rssi: i8 },
+    /// The device responded to discovery but not when asked for its signal strength.
+    Unresponsive { mac: String, ip: IpAddr },
+    /// Several devices responded from the same IP address.
+    DuplicateIp { ip: IpAddr, macs: Vec<String> },
+}
+
+impl Display for FleetIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OldFirmware {
+                mac,
+                ip,
+                module_name,
+                firmware_version,
+                latest,
+            } => write!(
+                f,
+                "{} ({}) runs firmware {} while other {} devices run {}",
+                mac, ip, firmware_version, module_name, latest
+            ),
+            Self::WeakSignal { mac, ip, rssi } => {
+                write!(f, "{} ({}) has a weak signal of {} dBm", mac, ip, rssi)
+            }
+            Self::Unresponsive { mac, ip } => {
+                write!(f, "{} ({}) did not report its signal strength", mac, ip)
+            }
+            Self::DuplicateIp { ip, macs } => {
+                write!(f, "{} is used by several devices: {}", ip, macs.join(", "))
+            }
+        }
+    }
+}
+
+/// Compares dotted version numbers such as `1.25.0` part by part, numerically where possible.
+/// Missing parts count as 0, so `1.25` equals `1.25.0`, and parts that are not numbers sort after
+/// numbers and compare as text.
+fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    let parts = |version: &str| {
+        version
+            .split('.')
+            .map(|part| part.parse::<u64>().map_err(|_| part.to_string()))
+            .collect::<Vec<_>>()
+    };
+    let (a, b) = (parts(a), parts(b));
+    (0..a.len().max(b.len()))
+        .map(|i| {
+            let part = |parts: &[Result<u64, String>]| parts.get(i).cloned().unwrap_or(Ok(0));
+            part(&a).cmp(&part(&b))
+        })
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(std::cmp::Ordering::Equal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cmp::Ordering;
+
+    #[test]
+    fn compares_versions_numerically() {
+        assert_eq!(compare_versions("1.25.0", "1.25.0"), Ordering::Equal);
+        assert_eq!(compare_versions("1.9.0", "1.10.0"), Ordering::Less);
+        assert_eq!(compare_versions("1.22.0", "1.21.5"), Ordering::Greater);
+        assert_eq!(compare_versions("2.0", "10.0"), Ordering::Less);
+    }
+
+    #[test]
+    fn compares_versions_of_different_lengths() {
+        assert_eq!(compare_versions("1.25", "1.25.0"), Ordering::Equal);
+        assert_eq!(compare_versions("1.25", "1.25.1"), Ordering::Less);
+        assert_eq!(compare_versions("1.26", "1.25.9"), Ordering::Greater);
+        assert_eq!(compare_versions("1.25.0.0", "1.25"), Ordering::Equal);
+    }
+
+    #[test]
+    fn compares_non_numeric_parts_as_text() {
+        assert_eq!(compare_versions("1.25.beta", "1.25.beta"), Ordering::Equal);
+        assert_eq!(compare_versions("1.25.alpha", "1.25.beta"), Ordering::Less);
+        assert_eq!(compare_versions("1.25.beta", "1.25.9"), Ordering::Greater);
+        assert_eq!(compare_versions("1.24.beta", "1.25.0"), Ordering::Less);
+    }
+}
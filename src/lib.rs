@@ -1,3 +1,13 @@
+pub mod brightness;
+pub mod capabilities;
 pub mod color;
-mod connection;
+pub mod connection;
 pub mod devices;
+pub mod duration;
+pub mod fans;
+pub mod fleet;
+pub mod products;
+pub mod provision;
+pub mod scenes;
+pub mod state;
+pub mod user_config;
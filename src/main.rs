@@ -1,46 +1,365 @@
-use clap::{Parser, Subcommand};
+mod cli;
+
+use clap::{ArgAction, CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::{ArgValueCandidates, CompleteEnv, Shell};
+use cli::adjustment::Adjustment;
+use cli::cache::DeviceCache;
+use cli::completion::{alias_candidates, device_candidates, ip_candidates};
+use cli::output::{OutputError, OutputFormat};
+use cli::records::{
+    ActionRecord, AliasRecord, ChangeRecord, DeviceRecord, FieldRecord, SettingRecord,
+};
+use cli::script::{Line, ScriptError, Statement};
+use cli::selector::{parse_alias_name, Aliases, Selector, SelectorError};
+use cli::signal::rssi_to_signal_strength;
+#[cfg(feature = "tui")]
+use cli::tui::TuiError;
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
 use std::net::IpAddr;
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
+use std::thread;
+use std::time::{Duration, Instant};
+use std::{fs, io::Read};
 use tabled::{builder::Builder, settings::Style};
-use wizctl::color::RGBCW;
+use wizctl::brightness::Brightness;
+use wizctl::color::{Color, RGBCW};
+use wizctl::connection::{ConnectionBuilder, ConnectionError};
 use wizctl::devices::{Device, DeviceError};
+use wizctl::duration::parse_duration;
+use wizctl::fans::FanMode;
+use wizctl::fleet::FleetReport;
+use wizctl::provision::{ProvisionError, ProvisionerBuilder, DEFAULT_AP_ADDRESS};
+use wizctl::scenes::Scene;
+use wizctl::state::LightState;
+use wizctl::user_config::{UserConfig, UserConfigSetting};
 
 use thiserror::Error;
 
+/// Exit code for errors, distinct from the failure that `wizctl get` uses for a device that is off,
+/// so that scripts can tell the two apart.
+const ERROR_EXIT_CODE: u8 = 2;
+
 fn main() -> ExitCode {
+    CompleteEnv::with_factory(Cli::command).complete();
     let cli = Cli::parse();
+    init_logging(cli.verbose);
+    let connection = ConnectionBuilder::new()
+        .timeout(cli.timeout)
+        .retries(cli.retries)
+        .discovery_window(cli.discovery_window);
+
+    match run(&cli.command, &connection, cli.output) {
+        Ok(exit_code) => exit_code,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::from(ERROR_EXIT_CODE)
+        }
+    }
+}
 
-    let result = match &cli.command {
-        Command::List => list_devices(),
+fn run(
+    command: &Command,
+    connection: &ConnectionBuilder,
+    output: OutputFormat,
+) -> Result<ExitCode, CliError> {
+    match command {
+        Command::List {
+            interfaces,
+            identify,
+        } => list_devices(
+            &connection_builder(connection, interfaces),
+            *identify,
+            output,
+        ),
+        Command::Get { device, field } => {
+            return get_field(&device.resolve()?, connection, *field, output)
+        }
+        Command::Identify { device } => identify_device(&device.resolve()?, connection, output),
+        Command::Audit { interfaces } => {
+            audit_devices(&connection_builder(connection, interfaces), output)
+        }
+        Command::Completions { shell } => print_completions(*shell),
+        Command::Run { script, keep_going } => run_script(script, *keep_going, connection, output),
+        #[cfg(feature = "tui")]
+        Command::Tui { interfaces } => {
+            cli::tui::run(&connection_builder(connection, interfaces)).map_err(CliError::from)
+        }
+        Command::Alias { command } => match command {
+            AliasCommand::List => list_aliases(output),
+            AliasCommand::Set { name, ip } => set_alias(name, ip),
+            AliasCommand::Remove { name } => remove_alias(name),
+        },
+        Command::Config { command } => match command {
+            ConfigCommand::Get { device } => get_config(&device.resolve()?, connection, output),
+            ConfigCommand::Set { device, settings } => {
+                set_config(&device.resolve()?, connection, settings, output)
+            }
+        },
+        Command::Provision {
+            ssid,
+            psk_file,
+            ap,
+            wait,
+        } => provision_device(ssid, psk_file, ap, *wait, connection, output),
+        Command::Toggle { device } => toggle_device(&device.resolve()?, connection, output),
+        Command::Reboot { device } => reboot_device(&device.resolve()?, connection, output),
+        Command::Reset { device, yes_really } => {
+            reset_device(&device.resolve()?, connection, *yes_really, output)
+        }
+        Command::Raw {
+            device,
+            method,
+            params,
+        } => call_raw(&device.resolve()?, connection, method, params, output),
         //Command::Inspect { ip } => inspect_device(ip),
         Command::Set {
-            ip,
+            device,
             on,
             off,
             rgbcw,
+            color,
+            scene,
+            kelvin,
             brightness,
-        } => set_device(ip, on, off, rgbcw, brightness),
-    };
-
-    if let Err(e) = result {
-        eprintln!("{}", e);
-        ExitCode::FAILURE
-    } else {
-        ExitCode::SUCCESS
-    }
+            ratio,
+        } => set_device(
+            &device.resolve()?,
+            connection,
+            SetOptions {
+                on: *on,
+                off: *off,
+                rgbcw: rgbcw.to_owned(),
+                color: color.to_owned(),
+                scene: *scene,
+                kelvin: *kelvin,
+                brightness: *brightness,
+                ratio: *ratio,
+            },
+            output,
+        ),
+        Command::Fan {
+            device,
+            on,
+            off,
+            speed,
+            reverse,
+            forward,
+            mode,
+        } => set_fan(
+            &device.resolve()?,
+            connection,
+            FanOptions {
+                on: *on,
+                off: *off,
+                speed: *speed,
+                reverse: *reverse,
+                forward: *forward,
+                mode: *mode,
+            },
+            output,
+        ),
+    }?;
+    Ok(ExitCode::SUCCESS)
 }
 
 /// Controls WiZ Connected devices
 #[derive(Parser)]
 struct Cli {
+    #[clap(
+        long,
+        short,
+        global = true,
+        value_enum,
+        default_value_t = OutputFormat::Table,
+        help = "Format to print results in"
+    )]
+    output: OutputFormat,
+
+    #[clap(
+        long,
+        global = true,
+        value_parser = parse_duration,
+        default_value = "2s",
+        help = "How long to wait for a device to respond (e.g. \"500ms\", \"5s\")"
+    )]
+    timeout: Duration,
+
+    #[clap(
+        long,
+        global = true,
+        default_value_t = 0,
+        help = "How many times to resend a request that gets no response. Reboot, reset, Wi-Fi and user config changes and pulses are never resent"
+    )]
+    retries: u32,
+
+    #[clap(
+        long,
+        global = true,
+        value_parser = parse_duration,
+        default_value = "2s",
+        help = "How long to wait for devices to answer discovery (e.g. \"5s\")"
+    )]
+    discovery_window: Duration,
+
+    #[clap(
+        short,
+        long,
+        global = true,
+        action = ArgAction::Count,
+        help = "Logs each request and response with its timing (-v), or everything (-vv)"
+    )]
+    verbose: u8,
+
     #[command(subcommand)]
     command: Command,
 }
 
+/// Logs to stderr at a level chosen by the number of -v flags, or by `RUST_LOG` if it is set.
+fn init_logging(verbose: u8) {
+    let level = match verbose {
+        0 => log::LevelFilter::Warn,
+        1 => log::LevelFilter::Debug,
+        _ => log::LevelFilter::Trace,
+    };
+    env_logger::Builder::new()
+        .filter_level(level)
+        .parse_default_env()
+        .format_timestamp_millis()
+        .init();
+}
+
 #[derive(Subcommand)]
 enum Command {
     #[clap(about = "List all the available devices on the local network")]
-    List,
+    List {
+        #[clap(
+            long = "interface",
+            required = false,
+            help = "Name of a network interface to discover devices on (can be repeated)"
+        )]
+        interfaces: Vec<String>,
+
+        #[clap(
+            long,
+            required = false,
+            help = "Makes each device pulse in turn, waiting for Enter before moving to the next"
+        )]
+        identify: bool,
+    },
+    #[clap(
+        about = "Prints a single field of the state of a device, exiting with 1 if it is off or the field is empty, or 2 if it cannot be read"
+    )]
+    Get {
+        #[clap(add = ArgValueCandidates::new(device_candidates), help = "IP address, MAC address or alias of the device to read")]
+        device: Selector,
+
+        #[clap(value_enum, default_value_t = StateField::State, help = "Field to print")]
+        field: StateField,
+    },
+    #[clap(
+        about = "Groups all devices by module and firmware, and flags those that need attention"
+    )]
+    Audit {
+        #[clap(
+            long = "interface",
+            required = false,
+            help = "Name of a network interface to discover devices on (can be repeated)"
+        )]
+        interfaces: Vec<String>,
+    },
+    #[cfg(feature = "tui")]
+    #[clap(about = "Shows all devices in an interactive dashboard")]
+    Tui {
+        #[clap(
+            long = "interface",
+            required = false,
+            help = "Name of a network interface to discover devices on (can be repeated)"
+        )]
+        interfaces: Vec<String>,
+    },
+    #[clap(
+        about = "Prints a script that completes commands and device names in a shell",
+        long_about = "Prints a script that completes commands and device names in a shell. Load it on shell startup, e.g. with `source <(wizctl completions bash)` in ~/.bashrc. Devices are completed from aliases and from the devices found by the last `wizctl list`."
+    )]
+    Completions {
+        #[clap(value_enum, help = "Shell to generate the script for")]
+        shell: Shell,
+    },
+    #[clap(
+        about = "Runs the commands in a script, one per line",
+        long_about = "Runs the commands in a script, one per line. Any wizctl command can be used without the `wizctl`, along with:\n\n  sleep <duration>      Waits, e.g. `sleep 500ms` or `sleep 2s`\n  repeat [count]        Runs the lines up to `end` count times, or until stopped\n  end                   Closes a repeat\n  snapshot <device>     Remembers the state of a device\n  restore <device>      Returns a device to its last snapshot\n  fade <device> [--brightness <percent>] [--kelvin <kelvin>] --over <duration>\n                        Gradually changes the brightness and color temperature\n\nThe script stops at the first line that fails, unless the line starts with `try`. Lines starting with # are comments."
+    )]
+    Run {
+        #[clap(help = "Path to the script, or - to read it from stdin")]
+        script: PathBuf,
+
+        #[clap(
+            long,
+            help = "Continues with the next line when a line fails, as if every line started with `try`"
+        )]
+        keep_going: bool,
+    },
+    #[clap(about = "Makes a device pulse or blink so that it can be found")]
+    Identify {
+        #[clap(add = ArgValueCandidates::new(device_candidates), help = "IP address, MAC address or alias of the device to identify")]
+        device: Selector,
+    },
+    #[clap(about = "Names devices so that they can be used in place of their IP addresses")]
+    Alias {
+        #[command(subcommand)]
+        command: AliasCommand,
+    },
+    #[clap(about = "Reads or changes settings such as fade times and power-on behavior")]
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+    #[clap(
+        about = "Joins a factory-fresh device to a Wi-Fi network through its setup access point"
+    )]
+    Provision {
+        #[clap(long, help = "Name of the Wi-Fi network for the device to join")]
+        ssid: String,
+
+        #[clap(
+            long,
+            help = "File containing the passphrase of the Wi-Fi network, or \"-\" to read it from stdin"
+        )]
+        psk_file: PathBuf,
+
+        #[clap(
+            long,
+            default_value_t = DEFAULT_AP_ADDRESS,
+            help = "IP address of the device on its setup access point"
+        )]
+        ap: IpAddr,
+
+        #[clap(
+            long,
+            default_value_t = 60,
+            help = "Seconds to wait for the device to appear on the Wi-Fi network"
+        )]
+        wait: u64,
+    },
+    #[clap(about = "Restarts a device")]
+    Reboot {
+        #[clap(add = ArgValueCandidates::new(device_candidates), help = "IP address, MAC address or alias of the device to reboot")]
+        device: Selector,
+    },
+    #[clap(about = "Restores a device to its factory settings, including its Wi-Fi configuration")]
+    Reset {
+        #[clap(add = ArgValueCandidates::new(device_candidates), help = "IP address, MAC address or alias of the device to reset")]
+        device: Selector,
+
+        #[clap(
+            long,
+            required = false,
+            help = "Confirms that the device should be reset and set up again from scratch"
+        )]
+        yes_really: bool,
+    },
     //#[clap(about = "Inspects the state and configuration of a device on the local network")]
     //Inspect {
     //    #[clap(help = "IP address of the device to inspect")]
@@ -48,8 +367,8 @@ enum Command {
     //},
     #[clap(about = "Sets the color/state of a device")]
     Set {
-        #[clap(help = "IP address of the device to set")]
-        ip: IpAddr,
+        #[clap(add = ArgValueCandidates::new(device_candidates), help = "IP address, MAC address or alias of the device to set")]
+        device: Selector,
 
         #[clap(
             long,
@@ -75,51 +394,589 @@ enum Command {
         )]
         rgbcw: Option<RGBCW>,
 
+        #[clap(
+            long,
+            required = false,
+            conflicts_with_all = ["off", "rgbcw"],
+            help = "Sets the color by name, hex, HSV or temperature (e.g. \"orange\", \"#ff8800\", \"hsv(30,100%,100%)\", \"2700K\")"
+        )]
+        color: Option<Color>,
+
+        #[clap(
+            long,
+            required = false,
+            conflicts_with_all = ["off", "rgbcw", "color"],
+            help = "Sets a scene by name or ID (e.g. \"fireplace\", \"wake up\", \"5\")"
+        )]
+        scene: Option<Scene>,
+
+        #[clap(
+            long,
+            required = false,
+            allow_negative_numbers = true,
+            conflicts_with_all = ["off", "rgbcw", "color", "scene"],
+            help = "Sets the color temperature in Kelvin, or changes it relative to the current temperature (e.g. \"2700\", \"+500\", \"-500\")"
+        )]
+        kelvin: Option<Adjustment<u16>>,
+
+        #[clap(
+            long,
+            required = false,
+            allow_hyphen_values = true,
+            conflicts_with = "off",
+            help = "Sets the brightness in percent between 10 and 100, or changes it relative to the current brightness (e.g. \"50\", \"+10\", \"-10\")"
+        )]
+        brightness: Option<Adjustment<Brightness>>,
+
         #[clap(
             long,
             required = false,
             conflicts_with = "off",
-            help = "Sets the brightness with a values between 0 and 255"
+            help = "Sets the balance between the up (100) and down (0) lights of a dual-head fixture"
         )]
-        brightness: Option<u8>,
+        ratio: Option<u8>,
+    },
+    #[clap(about = "Turns a device off if it is on, and on otherwise")]
+    Toggle {
+        #[clap(add = ArgValueCandidates::new(device_candidates), help = "IP address, MAC address or alias of the device to toggle")]
+        device: Selector,
     },
+    #[clap(about = "Sets the state of the fan of a fan/light combo device")]
+    Fan {
+        #[clap(add = ArgValueCandidates::new(device_candidates), help = "IP address, MAC address or alias of the device to set")]
+        device: Selector,
+
+        #[clap(
+            long,
+            required = false,
+            conflicts_with = "off",
+            help = "Turns the fan on"
+        )]
+        on: bool,
+
+        #[clap(
+            long,
+            required = false,
+            conflicts_with = "on",
+            help = "Turns the fan off"
+        )]
+        off: bool,
+
+        #[clap(
+            long,
+            required = false,
+            conflicts_with = "off",
+            help = "Sets the fan speed, starting from 1 for the slowest"
+        )]
+        speed: Option<u8>,
+
+        #[clap(
+            long,
+            required = false,
+            conflicts_with = "forward",
+            help = "Makes the fan spin in reverse"
+        )]
+        reverse: bool,
+
+        #[clap(
+            long,
+            required = false,
+            conflicts_with = "reverse",
+            help = "Makes the fan spin forward"
+        )]
+        forward: bool,
+
+        #[clap(
+            long,
+            required = false,
+            conflicts_with = "off",
+            help = "Sets the fan mode (\"normal\" or \"breeze\")"
+        )]
+        mode: Option<FanMode>,
+    },
+    #[clap(
+        about = "Sends a request for any method to a device and prints the response",
+        long_about = "Sends a request for any method to a device and prints the response, so that methods wizctl does not support yet can be tried (e.g. `wizctl raw porch getPilot` or `wizctl raw porch setPilot '{\"state\": true}'`)"
+    )]
+    Raw {
+        #[clap(add = ArgValueCandidates::new(device_candidates), help = "IP address, MAC address or alias of the device to send the request to")]
+        device: Selector,
+
+        #[clap(help = "Name of the method to call (e.g. \"getPilot\")")]
+        method: String,
+
+        #[clap(
+            default_value = "{}",
+            value_parser = parse_json,
+            help = "Params of the request as JSON"
+        )]
+        params: serde_json::Value,
+    },
+}
+
+/// Fields that `wizctl get` can print.
+#[derive(Clone, Copy, ValueEnum)]
+enum StateField {
+    /// "on" or "off"
+    State,
+    /// Brightness in percent
+    Brightness,
+    /// Color, as a temperature or RGBCW value
+    Color,
+    /// Color temperature, if the device is set to white light
+    Kelvin,
+    /// Name of the scene, if one is set
+    Scene,
+    /// Signal strength in dBm
+    Rssi,
+    /// Power consumption in watts
+    Power,
+}
+
+#[derive(Subcommand)]
+enum AliasCommand {
+    #[clap(about = "Lists all aliases")]
+    List,
+    #[clap(about = "Names a device, replacing any existing alias with the same name")]
+    Set {
+        #[clap(value_parser = parse_alias_name, help = "Name for the device (e.g. \"porch\")")]
+        name: String,
+
+        #[clap(add = ArgValueCandidates::new(ip_candidates), help = "IP address of the device")]
+        ip: IpAddr,
+    },
+    #[clap(about = "Removes an alias")]
+    Remove {
+        #[clap(add = ArgValueCandidates::new(alias_candidates), help = "Name of the alias to remove")]
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    #[clap(about = "Shows the settings of a device")]
+    Get {
+        #[clap(add = ArgValueCandidates::new(device_candidates), help = "IP address, MAC address or alias of the device to read")]
+        device: Selector,
+    },
+    #[clap(about = "Changes settings of a device, leaving the others as they are")]
+    Set {
+        #[clap(add = ArgValueCandidates::new(device_candidates), help = "IP address, MAC address or alias of the device to change")]
+        device: Selector,
+
+        #[clap(
+            required = true,
+            help = "Settings to change (e.g. \"fade-in=500ms\", \"fade-out=1.5s\", \"power-on-restore=on\", \"default-brightness=60\")"
+        )]
+        settings: Vec<UserConfigSetting>,
+    },
+}
+
+/// Restricts a connection to discover devices on the given network interfaces, or leaves it
+/// discovering on all of them if none are given.
+fn connection_builder(connection: &ConnectionBuilder, interfaces: &[String]) -> ConnectionBuilder {
+    interfaces
+        .iter()
+        .fold(connection.clone(), |builder, name| builder.interface(name))
 }
 
-fn list_devices() -> Result<(), CliError> {
-    let mut devices = Device::discover()?;
+fn list_devices(
+    connection: &ConnectionBuilder,
+    identify: bool,
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    let mut devices = Vec::new();
+    for device in Device::discover_with(connection)? {
+        match device {
+            Ok(device) => devices.push(device),
+            Err(e) => eprintln!("Skipping a device that could not be set up: {}", e),
+        }
+    }
     devices.sort_by_key(|l| *l.ip());
-    println!("Found {} devices on the local network", devices.len());
-
-    let mut builder = Builder::default();
-    builder.push_record(vec!["MAC", "IP", "Type", "Signal"]);
-    for device in devices {
-        builder.push_record(vec![
-            device.mac(),
-            &device.ip().to_string(),
-            &format!("{}", device.kind()),
-            device
-                .get_rssi()
-                .map(rssi_to_signal_strength)
-                .unwrap_or("".to_string())
-                .as_ref(),
-        ]);
-    }
-    let table = builder.build().with(Style::rounded()).to_string();
-    println!("{}", table);
+    Device::load_capabilities(&devices);
+    if let Err(e) = DeviceCache::new(&devices).save() {
+        eprintln!("Could not remember the devices for shell completion: {}", e);
+    }
+
+    let records: Vec<DeviceRecord> = devices.iter().map(DeviceRecord::new).collect();
+    output.print(&records, |records| {
+        let mut builder = Builder::default();
+        builder.push_record(vec!["MAC", "IP", "Interface", "Type", "Signal"]);
+        for record in records {
+            builder.push_record(vec![
+                record.mac.clone(),
+                record.ip.to_string(),
+                record.interface.clone().unwrap_or_default(),
+                record.model_name.clone(),
+                record.rssi.map(rssi_to_signal_strength).unwrap_or_default(),
+            ]);
+        }
+        format!(
+            "Found {} devices on the local network\n{}",
+            records.len(),
+            builder.build().with(Style::rounded())
+        )
+    })?;
+
+    if identify {
+        identify_devices(&devices)?;
+    }
+
+    Ok(())
+}
+
+fn print_completions(shell: Shell) -> Result<(), CliError> {
+    cli::completion::write_registration(shell, &mut io::stdout()).map_err(CliError::CompletionError)
+}
+
+/// Time between the steps of a fade in a script.
+const FADE_STEP: Duration = Duration::from_millis(200);
+
+/// A wizctl command on a line of a script.
+#[derive(Parser)]
+#[command(name = "wizctl", no_binary_name = true)]
+struct ScriptCommand {
+    #[command(subcommand)]
+    command: Command,
+}
+
+fn parse_script_command(words: &[String]) -> Result<Command, String> {
+    let command = ScriptCommand::try_parse_from(words)
+        .map_err(|e| cli::script::clap_message(&e))?
+        .command;
+    match command {
+        Command::Run { .. } | Command::Completions { .. } => {
+            Err(format!("{} cannot be used in scripts!", words[0]))
+        }
+        #[cfg(feature = "tui")]
+        Command::Tui { .. } => Err(format!("{} cannot be used in scripts!", words[0])),
+        command => Ok(command),
+    }
+}
+
+/// Runs a script, checking all of it before running the first line.
+fn run_script(
+    path: &Path,
+    keep_going: bool,
+    connection: &ConnectionBuilder,
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    let text = cli::script::read(path)?;
+    let lines = cli::script::parse(&text, parse_script_command)?;
+    ScriptRunner {
+        connection: connection.clone(),
+        output,
+        keep_going,
+        snapshots: HashMap::new(),
+    }
+    .run_lines(&lines)
+}
 
+struct ScriptRunner {
+    connection: ConnectionBuilder,
+    output: OutputFormat,
+    keep_going: bool,
+    snapshots: HashMap<IpAddr, LightState>,
+}
+
+impl ScriptRunner {
+    fn run_lines(&mut self, lines: &[Line<Command>]) -> Result<(), CliError> {
+        for line in lines {
+            let error = match self.run_statement(&line.statement) {
+                Ok(()) => continue,
+                // Errors from the body of a repeat already name their line
+                Err(e @ CliError::ScriptLineFailed { .. }) => e,
+                Err(e) => CliError::ScriptLineFailed {
+                    line: line.number,
+                    source: Box::new(e),
+                },
+            };
+            if line.ignore_errors || self.keep_going {
+                eprintln!("{}", error);
+            } else {
+                return Err(error);
+            }
+        }
+        Ok(())
+    }
+
+    fn run_statement(&mut self, statement: &Statement<Command>) -> Result<(), CliError> {
+        match statement {
+            Statement::Command(command) => run(command, &self.connection, self.output).map(|_| ()),
+            Statement::Sleep(duration) => {
+                thread::sleep(*duration);
+                Ok(())
+            }
+            Statement::Repeat { count, body } => match count {
+                Some(count) => (0..*count).try_for_each(|_| self.run_lines(body)),
+                None => loop {
+                    self.run_lines(body)?;
+                },
+            },
+            Statement::Snapshot(selector) => {
+                let device = Device::connect_with(&self.connection, selector.resolve()?)?;
+                self.snapshots.insert(*device.ip(), device.get_state()?);
+                self.output
+                    .print(&[ActionRecord::new(&device, "snapshot")], |_| {
+                        format!("Saved the state of the device at {}", device.ip())
+                    })
+                    .map_err(CliError::from)
+            }
+            Statement::Restore(selector) => {
+                let ip = selector.resolve()?;
+                let state = self
+                    .snapshots
+                    .get(&ip)
+                    .ok_or_else(|| CliError::NoSnapshot(selector.to_string()))?;
+                let device = Device::connect_with(&self.connection, ip)?
+                    .set_pilot()
+                    .restore(state)
+                    .send()?;
+                self.output
+                    .print(&[ActionRecord::new(&device, "restore")], |_| {
+                        format!("Restored the state of the device at {}", ip)
+                    })
+                    .map_err(CliError::from)
+            }
+            Statement::Fade(args) => {
+                let device = fade_device(
+                    &args.device.resolve()?,
+                    &self.connection,
+                    args.brightness,
+                    args.kelvin,
+                    args.over,
+                )?;
+                self.output
+                    .print(&[ActionRecord::new(&device, "fade")], |_| {
+                        format!("Faded the device at {}", device.ip())
+                    })
+                    .map_err(CliError::from)
+            }
+        }
+    }
+}
+
+/// Changes the brightness and color temperature of a device in steps, from their current values to
+/// the given ones. Devices that are not set to a color temperature change to the new one straight
+/// away.
+fn fade_device(
+    ip: &IpAddr,
+    connection: &ConnectionBuilder,
+    brightness: Option<Brightness>,
+    kelvin: Option<u16>,
+    over: Duration,
+) -> Result<Device, CliError> {
+    let mut device = Device::connect_with(connection, ip.to_owned())?;
+    let state = device.get_state()?;
+    let start_brightness = match brightness {
+        Some(_) => state
+            .brightness()
+            .ok_or_else(|| DeviceError::NoCurrentValue("brightness".to_string()))?,
+        None => 0,
+    };
+    let start_kelvin = match (kelvin, state.color()) {
+        (Some(_), Some(Color::Kelvin(current))) => Some(*current),
+        (kelvin, _) => kelvin,
+    };
+
+    let steps = (over.as_millis() / FADE_STEP.as_millis()).max(1) as u32;
+    let start = Instant::now();
+    for step in 1..=steps {
+        let between = |from: i32, to: i32| from + (to - from) * step as i32 / steps as i32;
+        let mut builder = device.set_pilot().on();
+        if let Some(brightness) = brightness {
+            let value = between(start_brightness.into(), brightness.percent().into());
+            builder = builder.brightness(Brightness::saturating(value))?;
+        }
+        if let (Some(kelvin), Some(start_kelvin)) = (kelvin, start_kelvin) {
+            builder = builder.kelvin(between(start_kelvin.into(), kelvin.into()) as u16)?;
+        }
+        device = builder.send()?;
+        thread::sleep((start + over * step / steps).saturating_duration_since(Instant::now()));
+    }
+    Ok(device)
+}
+
+/// Walks through the devices one by one, making each pulse once and waiting for the user to move
+/// on. Prompts go to stderr so that they do not mix with machine-readable output.
+fn identify_devices(devices: &[Device]) -> Result<(), CliError> {
+    let mut lines = io::stdin().lock().lines();
+    for (i, device) in devices.iter().enumerate() {
+        eprint!(
+            "[{}/{}] Identifying {} ({}), press Enter for the next device or q to stop: ",
+            i + 1,
+            devices.len(),
+            device.ip(),
+            device.fetch_model_name()
+        );
+        io::stderr().flush().map_err(CliError::IoError)?;
+        if let Err(e) = device.identify() {
+            eprintln!("\nCould not identify the device at {}: {}", device.ip(), e);
+        }
+        match lines.next().transpose().map_err(CliError::IoError)? {
+            Some(line) if line.trim() != "q" => continue,
+            _ => break,
+        }
+    }
     Ok(())
 }
 
-fn rssi_to_signal_strength(rssi: i8) -> String {
-    if rssi < -70 {
-        "\u{2840} ".to_string()
-    } else if rssi < -60 {
-        "\u{28e0} ".to_string()
-    } else if rssi < -50 {
-        "\u{28e0}\u{2846}".to_string()
+/// Prints a single field, returning failure as the exit code if the device is off or the field
+/// has no value, so that it can be used as a condition in shell scripts.
+fn get_field(
+    ip: &IpAddr,
+    connection: &ConnectionBuilder,
+    field: StateField,
+    output: OutputFormat,
+) -> Result<ExitCode, CliError> {
+    let device = Device::connect_with(connection, ip.to_owned())?;
+    let state = || device.get_state();
+    let (value, truthy): (serde_json::Value, bool) = match field {
+        StateField::State => {
+            let on = *state()?.on();
+            (on.into(), on)
+        }
+        StateField::Brightness => option_value(*state()?.brightness()),
+        StateField::Color => option_value(state()?.color().as_ref().map(|color| match color {
+            // Without parentheses, so that it can be passed to `wizctl set --color`
+            Color::Rgbcw(rgbcw) => format!(
+                "{},{},{},{},{}",
+                rgbcw.r(),
+                rgbcw.g(),
+                rgbcw.b(),
+                rgbcw.c(),
+                rgbcw.w()
+            ),
+            color => color.to_string(),
+        })),
+        StateField::Kelvin => option_value(match state()?.color() {
+            Some(Color::Kelvin(kelvin)) => Some(*kelvin),
+            _ => None,
+        }),
+        StateField::Scene => option_value(state()?.scene().map(|scene| scene.to_string())),
+        StateField::Rssi => (state()?.rssi().to_owned().into(), true),
+        StateField::Power => {
+            let watts = f64::from(device.get_power()?) / 1000.0;
+            (watts.into(), true)
+        }
+    };
+
+    let record = FieldRecord {
+        ip: *ip,
+        field: field
+            .to_possible_value()
+            .expect("no field is skipped")
+            .get_name()
+            .to_string(),
+        value,
+    };
+    output.print(&[record], |records| match &records[0].value {
+        serde_json::Value::Bool(on) => (if *on { "on" } else { "off" }).to_string(),
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Null => String::new(),
+        value => value.to_string(),
+    })?;
+    Ok(if truthy {
+        ExitCode::SUCCESS
     } else {
-        "\u{28e0}\u{28fe}".to_string()
+        ExitCode::FAILURE
+    })
+}
+
+fn option_value<T: Into<serde_json::Value>>(value: Option<T>) -> (serde_json::Value, bool) {
+    match value {
+        Some(value) => (value.into(), true),
+        None => (serde_json::Value::Null, false),
+    }
+}
+
+fn list_aliases(output: OutputFormat) -> Result<(), CliError> {
+    let records: Vec<AliasRecord> = Aliases::load()?
+        .iter()
+        .map(|(name, ip)| AliasRecord {
+            name: name.clone(),
+            ip: *ip,
+        })
+        .collect();
+    output.print(&records, |records| {
+        let mut builder = Builder::default();
+        builder.push_record(vec!["Alias", "IP"]);
+        for record in records {
+            builder.push_record(vec![record.name.clone(), record.ip.to_string()]);
+        }
+        builder.build().with(Style::rounded()).to_string()
+    })?;
+    Ok(())
+}
+
+fn set_alias(name: &str, ip: &IpAddr) -> Result<(), CliError> {
+    let mut aliases = Aliases::load()?;
+    aliases.insert(name.to_string(), *ip);
+    aliases.save()?;
+    eprintln!("{} now refers to {}", name, ip);
+    Ok(())
+}
+
+fn remove_alias(name: &str) -> Result<(), CliError> {
+    let mut aliases = Aliases::load()?;
+    let ip = aliases
+        .remove(name)
+        .ok_or_else(|| SelectorError::UnknownAlias(name.to_string()))?;
+    aliases.save()?;
+    eprintln!("Removed alias {} for {}", name, ip);
+    Ok(())
+}
+
+fn identify_device(
+    ip: &IpAddr,
+    connection: &ConnectionBuilder,
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    let device = Device::connect_with(connection, ip.to_owned())?;
+    device.identify()?;
+    output.print(&[ActionRecord::new(&device, "identify")], |_| {
+        format!("Identified device at {}", ip)
+    })?;
+    Ok(())
+}
+
+fn audit_devices(connection: &ConnectionBuilder, output: OutputFormat) -> Result<(), CliError> {
+    let report = FleetReport::discover_with(connection)?;
+
+    // The full report only fits in a single document, so line-based formats list the devices
+    if output == OutputFormat::Json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).map_err(OutputError::from)?
+        );
+        return Ok(());
     }
+
+    output.print(report.devices(), |devices| {
+        let mut builder = Builder::default();
+        builder.push_record(vec!["Module", "Firmware", "Devices"]);
+        for group in report.groups() {
+            builder.push_record(vec![
+                group.module_name().to_string(),
+                group.firmware_version().to_string(),
+                group.macs().len().to_string(),
+            ]);
+        }
+        let mut lines = vec![
+            format!("Found {} devices on the local network", devices.len()),
+            builder.build().with(Style::rounded()).to_string(),
+        ];
+        if report.issues().is_empty() {
+            lines.push("No issues found".to_string());
+        } else {
+            lines.push(format!("{} issues found:", report.issues().len()));
+            for issue in report.issues() {
+                lines.push(format!("  {}", issue));
+            }
+        }
+        lines.join("\n")
+    })?;
+    Ok(())
 }
 
 //fn inspect_device(ip: &IpAddr) -> Result<()> {
@@ -132,24 +989,272 @@ fn rssi_to_signal_strength(rssi: i8) -> String {
 //    Ok(())
 //}
 
+fn get_config(
+    ip: &IpAddr,
+    connection: &ConnectionBuilder,
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    let device = Device::connect_with(connection, ip.to_owned())?;
+    let config = device.get_user_config()?;
+
+    let mut records: Vec<SettingRecord> = config
+        .settings()
+        .iter()
+        .map(|setting| SettingRecord {
+            ip: *ip,
+            setting: setting.key().to_string(),
+            value: setting.value(),
+        })
+        .collect();
+    if let Some(range) = config.white_range() {
+        records.push(SettingRecord {
+            ip: *ip,
+            setting: "white-range".to_string(),
+            value: format!("{}K-{}K", range.min(), range.max()),
+        });
+    }
+    output.print(&records, |records| {
+        let mut builder = Builder::default();
+        builder.push_record(vec!["Setting", "Value"]);
+        for record in records {
+            builder.push_record(vec![record.setting.as_str(), record.value.as_str()]);
+        }
+        builder.build().with(Style::rounded()).to_string()
+    })?;
+    Ok(())
+}
+
+fn set_config(
+    ip: &IpAddr,
+    connection: &ConnectionBuilder,
+    settings: &[UserConfigSetting],
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    let device = Device::connect_with(connection, ip.to_owned())?;
+    let config = settings
+        .iter()
+        .fold(UserConfig::new(), |config, setting| config.with(*setting));
+    device.set_user_config(&config)?;
+
+    let records: Vec<SettingRecord> = config
+        .settings()
+        .iter()
+        .map(|setting| SettingRecord {
+            ip: *ip,
+            setting: setting.key().to_string(),
+            value: setting.value(),
+        })
+        .collect();
+    output.print(&records, |records| {
+        records
+            .iter()
+            .map(|record| format!("Set {}={} at {}", record.setting, record.value, ip))
+            .collect::<Vec<_>>()
+            .join("\n")
+    })?;
+    Ok(())
+}
+
+fn provision_device(
+    ssid: &str,
+    psk_file: &Path,
+    ap: &IpAddr,
+    wait: u64,
+    connection: &ConnectionBuilder,
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    let psk = read_psk(psk_file).map_err(|source| CliError::UnreadablePsk {
+        path: psk_file.to_owned(),
+        source,
+    })?;
+    let provisioner = ProvisionerBuilder::new(ssid, psk)
+        .ap_address(*ap)
+        .connection(connection.clone())
+        .timeout(Duration::from_secs(wait))
+        .build();
+
+    progress(
+        output,
+        format!(
+            "Sending Wi-Fi credentials for \"{}\" to the device at {}",
+            ssid, ap
+        ),
+    );
+    let mac = provisioner.send_credentials()?;
+    progress(
+        output,
+        format!(
+            "Device {} is joining \"{}\", reconnect this computer to it if that does not happen automatically",
+            mac, ssid
+        ),
+    );
+    progress(
+        output,
+        format!("Waiting up to {}s for the device to appear", wait),
+    );
+    let device = provisioner.wait_for_device(&mac)?;
+    output.print(&[ActionRecord::new(&device, "provision")], |_| {
+        format!(
+            "Device {} joined \"{}\" at {} ({})",
+            mac,
+            ssid,
+            device.ip(),
+            device.fetch_model_name()
+        )
+    })?;
+    Ok(())
+}
+
+/// Reads a passphrase from a file or stdin, without the trailing line break.
+fn read_psk(path: &Path) -> io::Result<String> {
+    let mut psk = String::new();
+    if path == Path::new("-") {
+        io::stdin().read_to_string(&mut psk)?;
+    } else {
+        psk = fs::read_to_string(path)?;
+    }
+    Ok(psk.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Prints a progress message, on stderr if the output is machine-readable.
+fn progress(output: OutputFormat, message: String) {
+    if output.is_table() {
+        println!("{}", message);
+    } else {
+        eprintln!("{}", message);
+    }
+}
+
+fn reboot_device(
+    ip: &IpAddr,
+    connection: &ConnectionBuilder,
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    let device = Device::connect_with(connection, ip.to_owned())?;
+    device.reboot()?;
+    output.print(&[ActionRecord::new(&device, "reboot")], |_| {
+        format!("Rebooted device at {}", ip)
+    })?;
+    Ok(())
+}
+
+fn reset_device(
+    ip: &IpAddr,
+    connection: &ConnectionBuilder,
+    yes_really: bool,
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    if !yes_really {
+        eprintln!(
+            "Resetting the device at {} erases its Wi-Fi configuration, so it has to be set up again",
+            ip
+        );
+        eprintln!("Use `wizctl reset --yes-really {}` if you are sure", ip);
+        return Err(DeviceError::ResetNotConfirmed.into());
+    }
+    let device = Device::connect_with(connection, ip.to_owned())?;
+    let record = ActionRecord::new(&device, "reset");
+    device.factory_reset(yes_really)?;
+    output.print(&[record], |_| {
+        format!("Reset device at {} to its factory settings", ip)
+    })?;
+    Ok(())
+}
+
+fn parse_json(value: &str) -> Result<serde_json::Value, String> {
+    serde_json::from_str(value).map_err(|e| format!("Expected JSON, got: {}!\n{}", value, e))
+}
+
+fn call_raw(
+    ip: &IpAddr,
+    connection: &ConnectionBuilder,
+    method: &str,
+    params: &serde_json::Value,
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    let connection = connection
+        .clone()
+        .for_device(&(*ip).into())
+        .build()
+        .map_err(DeviceError::ClientInitError)?;
+    let response = connection.call_raw(*ip, method, params.clone())?;
+    // The response has no fixed shape, so it is always printed as JSON
+    let json = match output {
+        OutputFormat::Jsonl => serde_json::to_string(&response),
+        _ => serde_json::to_string_pretty(&response),
+    };
+    println!("{}", json.map_err(OutputError::from)?);
+    Ok(())
+}
+
+/// Changes requested with `wizctl set`.
+struct SetOptions {
+    on: bool,
+    off: bool,
+    rgbcw: Option<RGBCW>,
+    color: Option<Color>,
+    scene: Option<Scene>,
+    kelvin: Option<Adjustment<u16>>,
+    brightness: Option<Adjustment<Brightness>>,
+    ratio: Option<u8>,
+}
+
 fn set_device(
     ip: &IpAddr,
-    on: &bool,
-    off: &bool,
-    rgbcw: &Option<RGBCW>,
-    brightness: &Option<u8>,
+    connection: &ConnectionBuilder,
+    options: SetOptions,
+    output: OutputFormat,
 ) -> Result<(), CliError> {
-    let device = Device::connect(ip.to_owned())?;
+    let mut record = ChangeRecord::new(*ip);
+    record.on = (options.on || options.off).then_some(options.on);
+    record.color = options
+        .rgbcw
+        .as_ref()
+        .map(ToString::to_string)
+        .or_else(|| options.color.as_ref().map(ToString::to_string));
+    record.scene = options.scene.map(|scene| scene.to_string());
+    match options.kelvin {
+        Some(Adjustment::To(kelvin)) => record.kelvin = Some(kelvin),
+        Some(Adjustment::By(delta)) => record.kelvin_change = Some(delta),
+        None => {}
+    }
+    match options.brightness {
+        Some(Adjustment::To(brightness)) => record.brightness = Some(brightness.percent()),
+        Some(Adjustment::By(delta)) => record.brightness_change = Some(delta),
+        None => {}
+    }
+    record.ratio = options.ratio;
+
+    report_change(record, apply_set(ip, connection, options), output)
+}
+
+/// Sends the changes, returning the MAC address of the device and a message for each change.
+fn apply_set(
+    ip: &IpAddr,
+    connection: &ConnectionBuilder,
+    options: SetOptions,
+) -> Result<(String, Vec<String>), CliError> {
+    let SetOptions {
+        on,
+        off,
+        rgbcw,
+        color,
+        scene,
+        kelvin,
+        brightness,
+        ratio,
+    } = options;
+    let device = Device::connect_with(connection, ip.to_owned())?;
 
     let mut builder = device.set_pilot();
     let mut messages = Vec::new();
 
-    if *on {
+    if on {
         builder = builder.on();
         messages.push(format!("Turned on device at {}", ip));
     }
 
-    if *off {
+    if off {
         builder = builder.off();
         messages.push(format!("Turned off device at {}", ip));
     }
@@ -159,26 +1264,211 @@ fn set_device(
         messages.push(format!("Set color at {} to {}", ip, rgbcw))
     }
 
-    if let Some(brightness) = brightness {
-        builder = builder.brightness(*brightness)?;
-        messages.push(format!("Set brightness at {} to {}", ip, brightness));
+    if let Some(color) = color {
+        builder = builder.color(color.to_owned())?;
+        messages.push(format!("Set color at {} to {}", ip, color))
+    }
+
+    if let Some(scene) = scene {
+        builder = builder.scene(scene)?;
+        messages.push(format!("Set scene at {} to {}", ip, scene));
+    }
+
+    match kelvin {
+        Some(Adjustment::To(kelvin)) => {
+            builder = builder.kelvin(kelvin)?;
+            messages.push(format!("Set color temperature at {} to {}K", ip, kelvin));
+        }
+        Some(Adjustment::By(delta)) => {
+            builder = builder.kelvin_by(delta)?;
+            messages.push(format!(
+                "Changed color temperature at {} by {:+}K",
+                ip, delta
+            ));
+        }
+        None => {}
+    }
+
+    match brightness {
+        Some(Adjustment::To(brightness)) => {
+            builder = builder.brightness(brightness)?;
+            messages.push(format!("Set brightness at {} to {}", ip, brightness));
+        }
+        Some(Adjustment::By(delta)) => {
+            builder = builder.brightness_by(delta)?;
+            messages.push(format!("Changed brightness at {} by {:+}", ip, delta));
+        }
+        None => {}
+    }
+
+    if let Some(ratio) = ratio {
+        builder = builder.ratio(ratio)?;
+        messages.push(format!("Set up/down light ratio at {} to {}", ip, ratio));
     }
 
     let device = builder.send()?;
 
     if messages.is_empty() {
-        println!("No change was made to the device at {}", ip);
-        println!("Use `wizctl set --help` to see what you can set");
-    } else {
-        for msg in messages {
-            println!("{}", msg);
+        messages.push(format!("No change was made to the device at {}", ip));
+        messages.push("Use `wizctl set --help` to see what you can set".to_string());
+    }
+    Ok((device.mac().to_string(), messages))
+}
+
+fn toggle_device(
+    ip: &IpAddr,
+    connection: &ConnectionBuilder,
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    let mut record = ChangeRecord::new(*ip);
+    let result = apply_toggle(ip, connection).map(|(mac, on)| {
+        record.on = Some(on);
+        let message = format!("Turned {} device at {}", if on { "on" } else { "off" }, ip);
+        (mac, vec![message])
+    });
+    report_change(record, result, output)
+}
+
+/// Toggles the device, returning its MAC address and whether it is now on.
+fn apply_toggle(ip: &IpAddr, connection: &ConnectionBuilder) -> Result<(String, bool), CliError> {
+    let (builder, on) = Device::connect_with(connection, ip.to_owned())?
+        .set_pilot()
+        .toggle()?;
+    let device = builder.send()?;
+    Ok((device.mac().to_string(), on))
+}
+
+/// Changes requested with `wizctl fan`.
+struct FanOptions {
+    on: bool,
+    off: bool,
+    speed: Option<u8>,
+    reverse: bool,
+    forward: bool,
+    mode: Option<FanMode>,
+}
+
+fn set_fan(
+    ip: &IpAddr,
+    connection: &ConnectionBuilder,
+    options: FanOptions,
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    let mut record = ChangeRecord::new(*ip);
+    record.fan_on = (options.on || options.off).then_some(options.on);
+    record.fan_speed = options.speed;
+    record.fan_reverse = (options.reverse || options.forward).then_some(options.reverse);
+    record.fan_mode = options.mode.map(|mode| mode.to_string());
+
+    report_change(record, apply_fan(ip, connection, options), output)
+}
+
+/// Sends the fan changes, returning the MAC address of the device and a message for each change.
+fn apply_fan(
+    ip: &IpAddr,
+    connection: &ConnectionBuilder,
+    options: FanOptions,
+) -> Result<(String, Vec<String>), CliError> {
+    let FanOptions {
+        on,
+        off,
+        speed,
+        reverse,
+        forward,
+        mode,
+    } = options;
+    let device = Device::connect_with(connection, ip.to_owned())?;
+
+    let mut builder = device.set_pilot();
+    let mut messages = Vec::new();
+
+    if on {
+        builder = builder.fan_on()?;
+        messages.push(format!("Turned on fan at {}", ip));
+    }
+
+    if off {
+        builder = builder.fan_off()?;
+        messages.push(format!("Turned off fan at {}", ip));
+    }
+
+    if let Some(speed) = speed {
+        builder = builder.fan_speed(speed)?;
+        messages.push(format!("Set fan speed at {} to {}", ip, speed));
+    }
+
+    if reverse || forward {
+        builder = builder.fan_reverse(reverse)?;
+        messages.push(format!(
+            "Set fan direction at {} to {}",
+            ip,
+            if reverse { "reverse" } else { "forward" }
+        ));
+    }
+
+    if let Some(mode) = mode {
+        builder = builder.fan_mode(mode)?;
+        messages.push(format!("Set fan mode at {} to {}", ip, mode));
+    }
+
+    let device = builder.send()?;
+
+    if messages.is_empty() {
+        messages.push(format!("No change was made to the fan at {}", ip));
+        messages.push("Use `wizctl fan --help` to see what you can set".to_string());
+    }
+    Ok((device.mac().to_string(), messages))
+}
+
+/// Prints the outcome of `set` or `fan`. Failures are also printed as records in machine-readable
+/// formats, so that every device gets a result, and then returned.
+fn report_change(
+    mut record: ChangeRecord,
+    result: Result<(String, Vec<String>), CliError>,
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    match result {
+        Ok((mac, messages)) => {
+            record.mac = Some(mac);
+            record.success = true;
+            output.print(&[record], |_| messages.join("\n"))?;
+            Ok(())
+        }
+        Err(e) => {
+            if !output.is_table() {
+                record.error = Some(e.to_string());
+                output.print(&[record], |_| String::new())?;
+            }
+            Err(e)
         }
     }
-    Ok(())
 }
 
 #[derive(Error, Debug)]
 enum CliError {
     #[error("{0}")]
     DeviceError(#[from] DeviceError),
+    #[error("{0}")]
+    ProvisionError(#[from] ProvisionError),
+    #[error("{0}")]
+    OutputError(#[from] OutputError),
+    #[error("{0}")]
+    SelectorError(#[from] SelectorError),
+    #[error("Request failed!\n{0}")]
+    ConnectionError(#[from] ConnectionError),
+    #[cfg(feature = "tui")]
+    #[error("{0}")]
+    TuiError(#[from] TuiError),
+    #[error("Could not write the completion script!\n{0}")]
+    CompletionError(#[source] io::Error),
+    #[error("{0}")]
+    ScriptError(#[from] ScriptError),
+    #[error("Line {line} of the script failed!\n{source}")]
+    ScriptLineFailed { line: usize, source: Box<CliError> },
+    #[error("No snapshot of {0} was taken earlier in the script!")]
+    NoSnapshot(String),
+    #[error("Could not read from the terminal!\n{0}")]
+    IoError(#[source] io::Error),
+    #[error("Could not read the passphrase from {path}!\n{source}", path = path.display())]
+    UnreadablePsk { path: PathBuf, source: io::Error },
 }
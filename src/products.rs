@@ -0,0 +1,235 @@
+use derive_getters::Getters;
+use regex::Regex;
+use std::sync::OnceLock;
+
+use crate::{
+    capabilities::KelvinRange,
+    devices::{
+        BulbKind::{Color, DimmableWhite, TunableWhite},
+        DeviceKind::{
+            self, Bulb, Ceiling, Downlight, DualHead, Fan, Filament, LightStrip, Plug, Squire,
+            Switch,
+        },
+    },
+};
+
+/// A WiZ product line, recognized from the identifier in the middle of a module name, such as
+/// `SHRGB` in `ESP01_SHRGB_03`.
+#[derive(Debug, Getters)]
+pub struct Product {
+    /// Text that the identifier must contain.
+    #[getter(skip)]
+    pattern: &'static str,
+    /// Text that the module name must end with, or empty to match any ending.
+    #[getter(skip)]
+    suffix: &'static str,
+    name: &'static str,
+    kind: DeviceKind,
+    kelvin_range: Option<KelvinRange>,
+}
+
+impl Product {
+    /// Finds the product that a module name belongs to, or `None` if it is not recognized.
+    pub fn from_module_name(module_name: &str) -> Option<&'static Self> {
+        static MODULE_NAME: OnceLock<Regex> = OnceLock::new();
+        let identifier = MODULE_NAME
+            .get_or_init(|| Regex::new(r"^ESP\d{2}_(\w+)_\d{2}[A-Z]*$").expect("Invalid regex!"))
+            .captures(module_name)?
+            .get(1)?
+            .as_str();
+
+        PRODUCTS.iter().find(|product| {
+            identifier.contains(product.pattern) && module_name.ends_with(product.suffix)
+        })
+    }
+}
+
+const fn product(
+    pattern: &'static str,
+    suffix: &'static str,
+    name: &'static str,
+    kind: DeviceKind,
+    kelvin_range: Option<(u16, u16)>,
+) -> Product {
+    Product {
+        pattern,
+        suffix,
+        name,
+        kind,
+        kelvin_range: match kelvin_range {
+            Some((min, max)) => Some(KelvinRange::new(min, max)),
+            None => None,
+        },
+    }
+}
+
+const COLOR_RANGE: Option<(u16, u16)> = Some((2200, 6500));
+const WHITE_RANGE: Option<(u16, u16)> = Some((2700, 6500));
+
+/// Known products, in order of precedence. More specific patterns must come before the generic
+/// `RGB`, `TW` and `DW` entries at the end, since the first match is used.
+static PRODUCTS: &[Product] = &[
+    product("SOCKET", "", "WiZ Smart Plug", Plug, None),
+    product("FANDIMS", "", "WiZ Fan", Fan(DimmableWhite), None),
+    product("SWITCH", "", "WiZ Switch", Switch, None),
+    product("REMOTE", "", "WiZ Remote", Switch, None),
+    product("DHRGB", "", "WiZ Dual Head", DualHead(Color), COLOR_RANGE),
+    product(
+        "DHTW",
+        "",
+        "WiZ Dual Head",
+        DualHead(TunableWhite),
+        WHITE_RANGE,
+    ),
+    product("SQRGB", "", "WiZ Squire", Squire(Color), COLOR_RANGE),
+    product("RGBSQ", "", "WiZ Squire", Squire(Color), COLOR_RANGE),
+    product("RGBC", "", "WiZ Ceiling", Ceiling(Color), COLOR_RANGE),
+    product("TWC", "", "WiZ Ceiling", Ceiling(TunableWhite), WHITE_RANGE),
+    product("DWC", "", "WiZ Ceiling", Ceiling(DimmableWhite), None),
+    product("RGBD", "", "WiZ Downlight", Downlight(Color), COLOR_RANGE),
+    product(
+        "TWD",
+        "",
+        "WiZ Downlight",
+        Downlight(TunableWhite),
+        WHITE_RANGE,
+    ),
+    product("DWD", "", "WiZ Downlight", Downlight(DimmableWhite), None),
+    product(
+        "TW9",
+        "",
+        "WiZ Filament",
+        Filament(TunableWhite),
+        Some((2000, 5000)),
+    ),
+    product("DW9", "", "WiZ Filament", Filament(DimmableWhite), None),
+    product("RGB", "ABI", "WiZ Light Strip", LightStrip, COLOR_RANGE),
+    product("RGB", "", "WiZ Color", Bulb(Color), COLOR_RANGE),
+    product(
+        "TW",
+        "",
+        "WiZ Tunable White",
+        Bulb(TunableWhite),
+        WHITE_RANGE,
+    ),
+    product("DW", "", "WiZ Dimmable White", Bulb(DimmableWhite), None),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A module name reported by a real device, with the name, kind and color temperature range
+    /// of the product it should be recognized as.
+    type Case = (&'static str, &'static str, DeviceKind, Option<(u16, u16)>);
+
+    const CORPUS: &[Case] = &[
+        ("ESP01_SHRGB_03", "WiZ Color", Bulb(Color), COLOR_RANGE),
+        ("ESP01_SHRGB1C_31", "WiZ Color", Bulb(Color), COLOR_RANGE),
+        ("ESP03_SHRGB1C_01", "WiZ Color", Bulb(Color), COLOR_RANGE),
+        ("ESP03_SHRGBP_31", "WiZ Color", Bulb(Color), COLOR_RANGE),
+        (
+            "ESP56_SHTW3_01",
+            "WiZ Tunable White",
+            Bulb(TunableWhite),
+            WHITE_RANGE,
+        ),
+        (
+            "ESP15_SHTW1_01I",
+            "WiZ Tunable White",
+            Bulb(TunableWhite),
+            WHITE_RANGE,
+        ),
+        (
+            "ESP01_SHTW1C_31",
+            "WiZ Tunable White",
+            Bulb(TunableWhite),
+            WHITE_RANGE,
+        ),
+        (
+            "ESP05_SHDW_21",
+            "WiZ Dimmable White",
+            Bulb(DimmableWhite),
+            None,
+        ),
+        (
+            "ESP14_SHDW1C_01",
+            "WiZ Dimmable White",
+            Bulb(DimmableWhite),
+            None,
+        ),
+        (
+            "ESP20_SHRGBC_01",
+            "WiZ Ceiling",
+            Ceiling(Color),
+            COLOR_RANGE,
+        ),
+        (
+            "ESP06_SHDW9_01",
+            "WiZ Filament",
+            Filament(DimmableWhite),
+            None,
+        ),
+        (
+            "ESP17_SHTW9_01",
+            "WiZ Filament",
+            Filament(TunableWhite),
+            Some((2000, 5000)),
+        ),
+        (
+            "ESP20_SHRGB_01ABI",
+            "WiZ Light Strip",
+            LightStrip,
+            COLOR_RANGE,
+        ),
+        (
+            "ESP20_DHRGB_01B",
+            "WiZ Dual Head",
+            DualHead(Color),
+            COLOR_RANGE,
+        ),
+        ("ESP10_SOCKET_06", "WiZ Smart Plug", Plug, None),
+        ("ESP25_SOCKET_01", "WiZ Smart Plug", Plug, None),
+        ("ESP03_FANDIMS_31", "WiZ Fan", Fan(DimmableWhite), None),
+    ];
+
+    fn kelvin_range(range: Option<(u16, u16)>) -> Option<KelvinRange> {
+        range.map(|(min, max)| KelvinRange::new(min, max))
+    }
+
+    #[test]
+    fn recognizes_real_module_names() {
+        for (module_name, name, kind, range) in CORPUS {
+            let product = Product::from_module_name(module_name)
+                .unwrap_or_else(|| panic!("{} was not recognized", module_name));
+            assert_eq!(product.name(), *name, "{}", module_name);
+            assert_eq!(product.kind(), kind, "{}", module_name);
+            assert_eq!(
+                *product.kelvin_range(),
+                kelvin_range(*range),
+                "{}",
+                module_name
+            );
+        }
+    }
+
+    #[test]
+    fn ignores_unknown_module_names() {
+        assert!(Product::from_module_name("ESP99_UNKNOWN_01").is_none());
+        assert!(Product::from_module_name("not a module name").is_none());
+    }
+
+    #[test]
+    fn color_products_use_the_color_range() {
+        for product in PRODUCTS {
+            if product.kind().light() == Some(Color) || *product.kind() == LightStrip {
+                assert_eq!(
+                    *product.kelvin_range(),
+                    kelvin_range(COLOR_RANGE),
+                    "{}",
+                    product.pattern
+                );
+            }
+        }
+    }
+}
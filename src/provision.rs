@@ -0,0 +1,159 @@
+use std::{
+    io,
+    net::{IpAddr, Ipv4Addr},
+    thread,
+    time::{Duration, Instant},
+};
+
+use thiserror::Error;
+
+use crate::{
+    connection::{
+        messages::set_wifi_config::SetWifiConfigRequest, ConnectionBuilder, ConnectionError,
+    },
+    devices::Device,
+};
+
+/// Address of a factory-fresh device on the access point it creates for setup.
+pub const DEFAULT_AP_ADDRESS: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 168, 4, 1));
+
+/// Joins a factory-fresh device to a Wi-Fi network.
+///
+/// This happens in two steps, since the computer has to be connected to the access point of the
+/// device for the first and to the target network for the second: [`Self::send_credentials`]
+/// gives the device the credentials, and [`Self::wait_for_device`] discovers it on the target
+/// network.
+pub struct Provisioner {
+    ssid: String,
+    psk: String,
+    ap_address: IpAddr,
+    connection_builder: ConnectionBuilder,
+    timeout: Duration,
+}
+
+pub struct ProvisionerBuilder(Provisioner);
+
+impl ProvisionerBuilder {
+    pub fn new(ssid: impl Into<String>, psk: impl Into<String>) -> Self {
+        Self(Provisioner {
+            ssid: ssid.into(),
+            psk: psk.into(),
+            ap_address: DEFAULT_AP_ADDRESS,
+            connection_builder: ConnectionBuilder::new(),
+            timeout: Duration::from_secs(60),
+        })
+    }
+
+    pub fn build(self) -> Provisioner {
+        self.0
+    }
+
+    /// Address of the device on its access point, if it is not [`DEFAULT_AP_ADDRESS`].
+    pub fn ap_address(mut self, value: IpAddr) -> Self {
+        self.0.ap_address = value;
+        self
+    }
+
+    /// Connection used both to reach the device on its access point and to discover it on the
+    /// target network.
+    pub fn connection(mut self, value: ConnectionBuilder) -> Self {
+        self.0.connection_builder = value;
+        self
+    }
+
+    /// How long to keep looking for the device on the target network.
+    pub fn timeout(mut self, value: Duration) -> Self {
+        self.0.timeout = value;
+        self
+    }
+}
+
+impl Provisioner {
+    pub fn ssid(&self) -> &str {
+        &self.ssid
+    }
+
+    pub fn ap_address(&self) -> &IpAddr {
+        &self.ap_address
+    }
+
+    pub fn timeout(&self) -> &Duration {
+        &self.timeout
+    }
+
+    /// Sends the Wi-Fi credentials to the device on its access point, returning its MAC address
+    /// so that it can be recognized once it has joined the target network.
+    pub fn send_credentials(&self) -> Result<String, ProvisionError> {
+        let connection = self
+            .connection_builder
+            .clone()
+            .build()
+            .map_err(ProvisionError::ClientInitError)?;
+        let mac = connection
+            .get_system_config(self.ap_address)
+            .map_err(|e| ProvisionError::UnreachableDevice(self.ap_address, e))?
+            .result()
+            .mac()
+            .to_owned();
+        connection
+            .set_wifi_config(
+                self.ap_address,
+                SetWifiConfigRequest::new(&self.ssid, &self.psk),
+            )
+            .map_err(ProvisionError::CredentialsRejected)?;
+        Ok(mac)
+    }
+
+    /// Repeatedly discovers devices until the one with the given MAC address appears, or the
+    /// timeout runs out. Discovery errors are retried, since the network may still be coming up
+    /// while the computer switches back to it.
+    pub fn wait_for_device(&self, mac: &str) -> Result<Device, ProvisionError> {
+        let connection_builder = self
+            .connection_builder
+            .clone()
+            .min_discovery_window(MIN_DISCOVERY_WINDOW);
+        let start = Instant::now();
+        while start.elapsed() < self.timeout {
+            if let Some(device) = Device::discover_with(&connection_builder)
+                .into_iter()
+                .flatten()
+                .flatten()
+                .find(|device| device.mac().eq_ignore_ascii_case(mac))
+            {
+                return Ok(device);
+            }
+            thread::sleep(POLL_INTERVAL.min(self.timeout.saturating_sub(start.elapsed())));
+        }
+        Err(ProvisionError::DeviceNotFound {
+            mac: mac.to_string(),
+            ssid: self.ssid.clone(),
+        })
+    }
+
+    /// Sends the credentials and waits for the device to appear, for when the computer stays
+    /// connected to the target network while reaching the access point (e.g. through a second
+    /// network interface).
+    pub fn provision(&self) -> Result<Device, ProvisionError> {
+        let mac = self.send_credentials()?;
+        self.wait_for_device(&mac)
+    }
+}
+
+/// Pause between discovery attempts that did not find the device.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Shortest discovery window used while waiting, so that a short `--discovery-window` does not
+/// flood the network with broadcasts.
+const MIN_DISCOVERY_WINDOW: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Error)]
+pub enum ProvisionError {
+    #[error("Client failed to initialize!\n{0}")]
+    ClientInitError(#[source] io::Error),
+    #[error("Could not reach a device in setup mode at {0}! Is this computer connected to its access point?\n{1}")]
+    UnreachableDevice(IpAddr, #[source] ConnectionError),
+    #[error("Device did not accept the Wi-Fi credentials!\n{0}")]
+    CredentialsRejected(#[source] ConnectionError),
+    #[error("Device {mac} did not appear on network \"{ssid}\"! Check the passphrase and that this computer is connected to the network")]
+    DeviceNotFound { mac: String, ssid: String },
+}
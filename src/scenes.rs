@@ -0,0 +1,113 @@
+use std::{fmt::Display, str::FromStr};
+use thiserror::Error;
+
+/// A built-in light scene, identified on the wire by its `sceneId`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Scene {
+    id: u16,
+    name: &'static str,
+}
+
+impl Scene {
+    const fn new(id: u16, name: &'static str) -> Self {
+        Self { id, name }
+    }
+
+    pub fn id(&self) -> u16 {
+        self.id
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn from_id(id: u16) -> Option<Self> {
+        ALL.iter().find(|scene| scene.id == id).copied()
+    }
+
+    /// All scenes, which are supported by color devices.
+    pub fn all() -> &'static [Scene] {
+        ALL
+    }
+
+    /// Scenes that only use white light, which are supported by tunable white devices.
+    pub fn tunable_white() -> Vec<Scene> {
+        Self::with_ids(&[6, 9, 10, 11, 12, 13, 14, 15, 16, 18, 29, 30, 31, 32])
+    }
+
+    /// Scenes that only vary brightness, which are supported by dimmable white devices.
+    pub fn dimmable_white() -> Vec<Scene> {
+        Self::with_ids(&[9, 10, 13, 14, 29, 30, 31, 32])
+    }
+
+    fn with_ids(ids: &[u16]) -> Vec<Scene> {
+        ids.iter().filter_map(|id| Self::from_id(*id)).collect()
+    }
+}
+
+impl Display for Scene {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// Parses a scene from its ID or its name, ignoring case and spaces (e.g. `"wake up"`).
+impl FromStr for Scene {
+    type Err = SceneError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalize = |name: &str| name.replace([' ', '-', '_'], "").to_lowercase();
+        let name = normalize(s);
+        s.trim()
+            .parse::<u16>()
+            .ok()
+            .and_then(Self::from_id)
+            .or_else(|| {
+                ALL.iter()
+                    .find(|scene| normalize(scene.name) == name)
+                    .copied()
+            })
+            .ok_or_else(|| SceneError::UnknownScene(s.to_string()))
+    }
+}
+
+const ALL: &[Scene] = &[
+    Scene::new(1, "Ocean"),
+    Scene::new(2, "Romance"),
+    Scene::new(3, "Sunset"),
+    Scene::new(4, "Party"),
+    Scene::new(5, "Fireplace"),
+    Scene::new(6, "Cozy"),
+    Scene::new(7, "Forest"),
+    Scene::new(8, "Pastel Colors"),
+    Scene::new(9, "Wake Up"),
+    Scene::new(10, "Bedtime"),
+    Scene::new(11, "Warm White"),
+    Scene::new(12, "Daylight"),
+    Scene::new(13, "Cool White"),
+    Scene::new(14, "Night Light"),
+    Scene::new(15, "Focus"),
+    Scene::new(16, "Relax"),
+    Scene::new(17, "True Colors"),
+    Scene::new(18, "TV Time"),
+    Scene::new(19, "Plant Growth"),
+    Scene::new(20, "Spring"),
+    Scene::new(21, "Summer"),
+    Scene::new(22, "Fall"),
+    Scene::new(23, "Deep Dive"),
+    Scene::new(24, "Jungle"),
+    Scene::new(25, "Mojito"),
+    Scene::new(26, "Club"),
+    Scene::new(27, "Christmas"),
+    Scene::new(28, "Halloween"),
+    Scene::new(29, "Candlelight"),
+    Scene::new(30, "Golden White"),
+    Scene::new(31, "Pulse"),
+    Scene::new(32, "Steampunk"),
+];
+
+#[derive(Debug, Error)]
+pub enum SceneError {
+    #[error("Did not recognize scene: {0}!")]
+    UnknownScene(String),
+}
@@ -0,0 +1,48 @@
+use derive_getters::Getters;
+
+use crate::{
+    color::{Color, RGBCW},
+    connection::messages::get_pilot::GetPilotResponseResult,
+    scenes::Scene,
+};
+
+/// State of the light of a device, as reported by `getPilot`.
+#[derive(Clone, Debug, Getters)]
+pub struct LightState {
+    on: bool,
+    /// Brightness in percent, if the device is dimmable.
+    brightness: Option<u8>,
+    /// Color the device is set to, or `None` if it is set to a scene or has no color.
+    color: Option<Color>,
+    /// Scene the device is set to, if any.
+    scene: Option<Scene>,
+    /// Balance between the up and down lights, if the device is a dual-head fixture.
+    ratio: Option<u8>,
+    /// Signal strength in dBm.
+    rssi: i8,
+}
+
+impl LightState {
+    /// Reads the light fields of a `getPilot` result.
+    pub fn from_pilot(pilot: &GetPilotResponseResult) -> Self {
+        let channels = [pilot.r(), pilot.g(), pilot.b(), pilot.c(), pilot.w()];
+        let color = if let Some(kelvin) = pilot.temp() {
+            Some(Color::Kelvin(*kelvin))
+        } else if channels.iter().all(|channel| channel.is_none()) {
+            None
+        } else {
+            let [r, g, b, c, w] = channels.map(|channel| channel.unwrap_or(0));
+            Some(Color::Rgbcw(RGBCW::new(r, g, b, c, w)))
+        };
+
+        Self {
+            on: *pilot.state(),
+            brightness: *pilot.dimming(),
+            color,
+            // Scene 0 means that no scene is set
+            scene: Scene::from_id(*pilot.scene_id()),
+            ratio: *pilot.ratio(),
+            rssi: *pilot.rssi(),
+        }
+    }
+}
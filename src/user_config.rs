@@ -0,0 +1,350 @@
+use derive_getters::Getters;
+use std::{fmt::Display, str::FromStr, time::Duration};
+use thiserror::Error;
+
+use crate::{
+    capabilities::KelvinRange,
+    connection::messages::{
+        get_user_config::GetUserConfigResponseResult,
+        set_user_config::{SetUserConfigRequest, SetUserConfigRequestBuilder},
+    },
+    duration::{parse_duration, DurationError},
+};
+
+/// Longest fade time that can be written, since devices take it in milliseconds as a 32-bit
+/// number.
+pub const MAX_FADE_TIME: Duration = Duration::from_millis(u32::MAX as u64);
+
+/// Settings of a device that persist across power cycles, such as fade times and how it behaves
+/// when power returns. Settings that are `None` are not reported by the device, or are left
+/// unchanged when the configuration is written.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Getters)]
+pub struct UserConfig {
+    /// Time taken to fade in when the device is turned on.
+    fade_in: Option<Duration>,
+    /// Time taken to fade out when the device is turned off.
+    fade_out: Option<Duration>,
+    /// Whether the device fades slowly when turned on or off at night.
+    fade_night: Option<bool>,
+    /// Brightness in percent that the device turns on at after a power cut.
+    default_brightness: Option<u8>,
+    /// Whether the device restores its previous state after a power cut, instead of turning on at
+    /// its default brightness.
+    power_on_restore: Option<bool>,
+    /// Lowest brightness in percent that the device can be dimmed to.
+    min_brightness: Option<u8>,
+    /// Range of color temperatures that the white LEDs are limited to. Read-only.
+    white_range: Option<KelvinRange>,
+}
+
+impl UserConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Changes a single setting.
+    pub fn with(mut self, setting: UserConfigSetting) -> Self {
+        match setting {
+            UserConfigSetting::FadeIn(value) => self.fade_in = Some(value),
+            UserConfigSetting::FadeOut(value) => self.fade_out = Some(value),
+            UserConfigSetting::FadeNight(value) => self.fade_night = Some(value),
+            UserConfigSetting::DefaultBrightness(value) => self.default_brightness = Some(value),
+            UserConfigSetting::PowerOnRestore(value) => self.power_on_restore = Some(value),
+            UserConfigSetting::MinBrightness(value) => self.min_brightness = Some(value),
+        }
+        self
+    }
+
+    /// The writable settings that are present, in a fixed order.
+    pub fn settings(&self) -> Vec<UserConfigSetting> {
+        [
+            self.fade_in.map(UserConfigSetting::FadeIn),
+            self.fade_out.map(UserConfigSetting::FadeOut),
+            self.fade_night.map(UserConfigSetting::FadeNight),
+            self.default_brightness
+                .map(UserConfigSetting::DefaultBrightness),
+            self.power_on_restore.map(UserConfigSetting::PowerOnRestore),
+            self.min_brightness.map(UserConfigSetting::MinBrightness),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    pub(crate) fn to_request(&self) -> Result<SetUserConfigRequest, UserConfigError> {
+        let mut builder = SetUserConfigRequestBuilder::new();
+        if let Some(value) = self.fade_in {
+            builder = builder.fade_in(fade_millis("fade-in", value)?);
+        }
+        if let Some(value) = self.fade_out {
+            builder = builder.fade_out(fade_millis("fade-out", value)?);
+        }
+        if let Some(value) = self.fade_night {
+            builder = builder.fade_night(value);
+        }
+        if let Some(value) = self.default_brightness {
+            builder = builder.dft_dim(value);
+        }
+        if let Some(value) = self.power_on_restore {
+            builder = builder.po(value);
+        }
+        if let Some(value) = self.min_brightness {
+            builder = builder.min_dimming(value);
+        }
+        Ok(builder.build())
+    }
+}
+
+impl From<&GetUserConfigResponseResult> for UserConfig {
+    fn from(value: &GetUserConfigResponseResult) -> Self {
+        Self {
+            fade_in: value.fade_in().map(|ms| Duration::from_millis(ms.into())),
+            fade_out: value.fade_out().map(|ms| Duration::from_millis(ms.into())),
+            fade_night: *value.fade_night(),
+            default_brightness: *value.dft_dim(),
+            power_on_restore: *value.po(),
+            min_brightness: *value.min_dimming(),
+            white_range: value
+                .white_range()
+                .map(|[min, max]| KelvinRange::new(min, max)),
+        }
+    }
+}
+
+/// A single writable setting, written as `key=value` (e.g. `fade-in=500ms`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UserConfigSetting {
+    FadeIn(Duration),
+    FadeOut(Duration),
+    FadeNight(bool),
+    DefaultBrightness(u8),
+    PowerOnRestore(bool),
+    MinBrightness(u8),
+}
+
+impl UserConfigSetting {
+    /// Names of the settings, as used by `FromStr` and `Display`.
+    pub const KEYS: &'static [&'static str] = &[
+        "fade-in",
+        "fade-out",
+        "fade-night",
+        "default-brightness",
+        "power-on-restore",
+        "min-brightness",
+    ];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            Self::FadeIn(_) => "fade-in",
+            Self::FadeOut(_) => "fade-out",
+            Self::FadeNight(_) => "fade-night",
+            Self::DefaultBrightness(_) => "default-brightness",
+            Self::PowerOnRestore(_) => "power-on-restore",
+            Self::MinBrightness(_) => "min-brightness",
+        }
+    }
+
+    /// The value of the setting, formatted so that it can be parsed back.
+    pub fn value(&self) -> String {
+        match self {
+            Self::FadeIn(value) | Self::FadeOut(value) => format!("{}ms", value.as_millis()),
+            Self::FadeNight(value) | Self::PowerOnRestore(value) => value.to_string(),
+            Self::DefaultBrightness(value) | Self::MinBrightness(value) => value.to_string(),
+        }
+    }
+}
+
+impl Display for UserConfigSetting {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}={}", self.key(), self.value())
+    }
+}
+
+/// Parses a `key=value` setting. Times need a unit (e.g. `500ms` or `1.5s`), flags accept `true`/`false`, `on`/`off` or `yes`/`no`, and brightness is a
+/// percentage.
+impl FromStr for UserConfigSetting {
+    type Err = UserConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, value) = s
+            .split_once('=')
+            .ok_or_else(|| UserConfigError::MissingValue(s.to_string()))?;
+        let key = key.trim().to_lowercase().replace('_', "-");
+        let value = value.trim();
+        let invalid = || UserConfigError::InvalidValue {
+            key: key.clone(),
+            value: value.to_string(),
+        };
+        match key.as_str() {
+            "fade-in" => {
+                let value =
+                    parse_duration(value).map_err(|source| UserConfigError::InvalidDuration {
+                        key: key.clone(),
+                        source,
+                    })?;
+                fade_millis(&key, value).map(|_| Self::FadeIn(value))
+            }
+            "fade-out" => {
+                let value =
+                    parse_duration(value).map_err(|source| UserConfigError::InvalidDuration {
+                        key: key.clone(),
+                        source,
+                    })?;
+                fade_millis(&key, value).map(|_| Self::FadeOut(value))
+            }
+            "fade-night" => parse_flag(value).map(Self::FadeNight).ok_or_else(invalid),
+            "default-brightness" => parse_percentage(value)
+                .map(Self::DefaultBrightness)
+                .ok_or_else(invalid),
+            "power-on-restore" => parse_flag(value)
+                .map(Self::PowerOnRestore)
+                .ok_or_else(invalid),
+            "min-brightness" => parse_percentage(value)
+                .map(Self::MinBrightness)
+                .ok_or_else(invalid),
+            _ => Err(UserConfigError::UnknownSetting(key)),
+        }
+    }
+}
+
+/// Converts a fade time to the milliseconds that devices take, if it is not too long.
+fn fade_millis(key: &str, value: Duration) -> Result<u32, UserConfigError> {
+    u32::try_from(value.as_millis()).map_err(|_| UserConfigError::FadeTimeTooLong {
+        key: key.to_string(),
+        value,
+    })
+}
+
+fn parse_flag(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "true" | "on" | "yes" | "1" => Some(true),
+        "false" | "off" | "no" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+fn parse_percentage(value: &str) -> Option<u8> {
+    value
+        .trim_end_matches('%')
+        .parse()
+        .ok()
+        .filter(|percentage| *percentage <= 100)
+}
+
+#[derive(Debug, Error)]
+pub enum UserConfigError {
+    #[error("Expected a setting in the form key=value, got: {0}!")]
+    MissingValue(String),
+    #[error("Did not recognize setting: {0}! Expected one of {keys}", keys = UserConfigSetting::KEYS.join(", "))]
+    UnknownSetting(String),
+    #[error("Invalid value for {key}: {value}!")]
+    InvalidValue { key: String, value: String },
+    #[error("Invalid time for {key}!\n{source}")]
+    InvalidDuration {
+        key: String,
+        #[source]
+        source: DurationError,
+    },
+    #[error("{key} of {value:?} is longer than the maximum of {MAX_FADE_TIME:?}!")]
+    FadeTimeTooLong { key: String, value: Duration },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_settings() {
+        let cases = [
+            (
+                "fade-in=500ms",
+                UserConfigSetting::FadeIn(Duration::from_millis(500)),
+            ),
+            (
+                "fade_out = 1.5s",
+                UserConfigSetting::FadeOut(Duration::from_millis(1500)),
+            ),
+            ("FADE-NIGHT=on", UserConfigSetting::FadeNight(true)),
+            (
+                "default-brightness=75%",
+                UserConfigSetting::DefaultBrightness(75),
+            ),
+            (
+                "power-on-restore=no",
+                UserConfigSetting::PowerOnRestore(false),
+            ),
+            ("min-brightness=10", UserConfigSetting::MinBrightness(10)),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                input.parse::<UserConfigSetting>().unwrap(),
+                expected,
+                "{input}"
+            );
+            assert_eq!(
+                expected.to_string().parse::<UserConfigSetting>().unwrap(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_settings() {
+        assert!(matches!(
+            "fade-in".parse::<UserConfigSetting>(),
+            Err(UserConfigError::MissingValue(_))
+        ));
+        assert!(matches!(
+            "fade=500ms".parse::<UserConfigSetting>(),
+            Err(UserConfigError::UnknownSetting(_))
+        ));
+        for input in ["fade-in=fast", "fade-in=500", "fade-out=-1s"] {
+            assert!(
+                matches!(
+                    input.parse::<UserConfigSetting>(),
+                    Err(UserConfigError::InvalidDuration { .. })
+                ),
+                "{input}"
+            );
+        }
+        for input in [
+            "fade-night=maybe",
+            "default-brightness=101",
+            "min-brightness=-5",
+        ] {
+            assert!(
+                matches!(
+                    input.parse::<UserConfigSetting>(),
+                    Err(UserConfigError::InvalidValue { .. })
+                ),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_fade_times_that_overflow() {
+        let max = format!("fade-in={}ms", MAX_FADE_TIME.as_millis());
+        assert_eq!(
+            max.parse::<UserConfigSetting>().unwrap(),
+            UserConfigSetting::FadeIn(MAX_FADE_TIME)
+        );
+        for input in [
+            format!("fade-in={}ms", MAX_FADE_TIME.as_millis() + 1),
+            "fade-out=5000000s".to_string(),
+        ] {
+            assert!(
+                matches!(
+                    input.parse::<UserConfigSetting>(),
+                    Err(UserConfigError::FadeTimeTooLong { .. })
+                ),
+                "{input}"
+            );
+        }
+        let config = UserConfig::new().with(UserConfigSetting::FadeOut(MAX_FADE_TIME * 2));
+        assert!(matches!(
+            config.to_request(),
+            Err(UserConfigError::FadeTimeTooLong { .. })
+        ));
+    }
+}